@@ -30,11 +30,13 @@ use tokio::time::sleep;
 use crate::features::connect_handler::ConnectionHandler;
 #[cfg(feature = "scheduler")]
 use crate::features::scheduler;
-use crate::handler::EventSender;
+use crate::handler::{module_order, EventSender, ModuleOrder};
 use crate::DeviceSource::{JsonFile, JsonString};
 use crate::{
-    show_slider, Authentication, ClientHandler, DeviceLockVerification, DeviceSource,
-    EventResultHandler, Module, SessionStore, ShowQR, ShowSliderTrait,
+    show_slider, Authentication, ChatQueues, ClientContext, ClientHandler, CommandPrefixes,
+    DataMap, DeviceLockVerification, DeviceSource, DispatchMode, EventResultHandler, Helps,
+    Middleware, Module, ModuleSwitchStore, ModuleSwitches, Permissions, SessionStore, ShowQR,
+    ShowSliderTrait, Waiters,
 };
 
 /// 客户端
@@ -44,6 +46,12 @@ pub struct Client {
     pub session_store: Arc<Option<Box<dyn SessionStore + Sync + Send>>>,
     pub(crate) modules: Arc<Vec<Module>>,
     pub(crate) result_handlers: Arc<Vec<EventResultHandler>>,
+    pub(crate) module_order: Arc<ModuleOrder>,
+    pub dispatch_mode: DispatchMode,
+    pub handler_timeout: Option<Duration>,
+    pub module_switches: Arc<ModuleSwitches>,
+    pub data: Arc<DataMap>,
+    pub(crate) context: Arc<ClientContext>,
     pub show_qr: ShowQR,
     pub show_slider: Arc<Box<dyn ShowSliderTrait + Sync + Send>>,
     pub shutting: bool,
@@ -76,6 +84,7 @@ pub async fn run_client(c: Arc<Client>) -> Result<()> {
             &mut jobs_scheduler,
             c.schedulers.clone(),
             c.rq_client.clone(),
+            c.context.clone(),
         )
         .await?;
         jobs_scheduler.start().await?;
@@ -110,6 +119,11 @@ pub async fn run_client_loop(c: Arc<Client>) -> Result<()> {
     let event_sender = EventSender {
         modules: c.modules.clone(),
         result_handlers: c.result_handlers.clone(),
+        module_order: c.module_order.clone(),
+        dispatch_mode: c.dispatch_mode,
+        handler_timeout: c.handler_timeout,
+        module_switches: c.module_switches.clone(),
+        context: c.context.clone(),
     };
     loop {
         // 每次轮询d
@@ -159,6 +173,7 @@ pub async fn run_client_once(c: Arc<Client>) -> Result<()> {
             &mut jobs_scheduler,
             c.schedulers.clone(),
             c.rq_client.clone(),
+            c.context.clone(),
         )
         .await?;
         jobs_scheduler.start().await?;
@@ -190,6 +205,11 @@ pub async fn run_client_once_inner(client: Arc<Client>) -> Result<()> {
     let event_sender = EventSender {
         modules: client.modules.clone(),
         result_handlers: client.result_handlers.clone(),
+        module_order: client.module_order.clone(),
+        dispatch_mode: client.dispatch_mode,
+        handler_timeout: client.handler_timeout,
+        module_switches: client.module_switches.clone(),
+        context: client.context.clone(),
     };
     loop_events(handle, &event_sender).await
 }
@@ -552,6 +572,13 @@ pub struct ClientBuilder {
     session_store: Arc<Option<Box<dyn SessionStore + Sync + Send>>>,
     modules_vec: Arc<Vec<Module>>,
     result_handlers_vec: Arc<Vec<EventResultHandler>>,
+    middlewares_vec: Arc<Vec<Box<dyn Middleware>>>,
+    dispatch_mode: DispatchMode,
+    handler_timeout: Option<Duration>,
+    module_switches: Arc<ModuleSwitches>,
+    command_prefixes: CommandPrefixes,
+    data_map: DataMap,
+    permissions: Permissions,
     #[cfg(feature = "scheduler")]
     schedulers: Arc<Vec<scheduler::Scheduler>>,
     show_qr: Option<ShowQR>,
@@ -573,6 +600,13 @@ impl ClientBuilder {
             session_store: Arc::new(None),
             modules_vec: Arc::new(vec![]),
             result_handlers_vec: Arc::new(vec![]),
+            middlewares_vec: Arc::new(vec![]),
+            dispatch_mode: DispatchMode::default(),
+            handler_timeout: None,
+            module_switches: Arc::new(ModuleSwitches::default()),
+            command_prefixes: CommandPrefixes::default(),
+            data_map: DataMap::default(),
+            permissions: Permissions::default(),
             #[cfg(feature = "scheduler")]
             schedulers: Arc::new(vec![]),
             show_qr: None,
@@ -601,6 +635,73 @@ impl ClientBuilder {
         self.result_handlers_vec = e.into();
         self
     }
+
+    /// 设置中间件, 按照设置的顺序调用
+    pub fn middlewares<M: Into<Arc<Vec<Box<dyn Middleware>>>>>(mut self, m: M) -> Self {
+        self.middlewares_vec = m.into();
+        self
+    }
+
+    /// 设置事件分发方式, 默认为按模块顺序依次调用
+    pub fn dispatch_mode(mut self, dispatch_mode: DispatchMode) -> Self {
+        self.dispatch_mode = dispatch_mode;
+        self
+    }
+
+    /// 设置handler默认的超时时间, 超时后取消handler并产生EventResult::Timeout, 默认不超时.
+    /// 可以使用 #[event(timeout = "10s")] 单独设置
+    pub fn handler_timeout<T: Into<Option<Duration>>>(mut self, timeout: T) -> Self {
+        self.handler_timeout = timeout.into();
+        self
+    }
+
+    /// 设置共享数据, 每个类型只能设置一个, 在handler中使用 Data<T> 作为参数获取
+    pub fn data<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.data_map.insert(value);
+        self
+    }
+
+    /// 设置超级用户的QQ号, 用于 #[event(require = "superuser")]
+    pub fn superusers<S: Into<Vec<i64>>>(mut self, superusers: S) -> Self {
+        self.permissions.superusers = superusers.into();
+        self
+    }
+
+    /// 设置没有权限时回复的消息, 其中的 {require} 会被替换为需要的权限, 默认不回复.
+    /// 可以使用 #[event(require_reply = "...")] 单独设置
+    pub fn permission_denied_reply<S: Into<Option<String>>>(mut self, reply: S) -> Self {
+        self.permissions.denied_reply = reply.into();
+        self
+    }
+
+    /// 设置模块开关, 需要在handler中开关模块时可以自己持有这个Arc
+    pub fn module_switches(mut self, module_switches: Arc<ModuleSwitches>) -> Self {
+        self.module_switches = module_switches;
+        self
+    }
+
+    /// 设置模块开关的存储方式
+    pub fn module_switch_store(
+        mut self,
+        module_switch_store: Box<dyn ModuleSwitchStore + Sync + Send>,
+    ) -> Self {
+        self.module_switches = Arc::new(ModuleSwitches::new(Some(module_switch_store)));
+        self
+    }
+
+    /// 添加全局的指令前缀 (例如 "!"), 可以添加多个,
+    /// 设置后bot_command需要以前缀或at机器人开头
+    pub fn command_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.command_prefixes.add_global(prefix);
+        self
+    }
+
+    /// 添加群的指令前缀, 设置后这个群不使用全局的前缀
+    pub fn group_command_prefix<S: Into<String>>(mut self, group_code: i64, prefix: S) -> Self {
+        self.command_prefixes.add_group(group_code, prefix);
+        self
+    }
+
     /// 设置定时任务
     #[cfg(feature = "scheduler")]
     pub fn schedulers<S: Into<Arc<Vec<scheduler::Scheduler>>>>(mut self, s: S) -> Self {
@@ -645,6 +746,19 @@ impl ClientBuilder {
 
     /// 构造客户端
     pub async fn build(&self) -> Result<Client, anyhow::Error> {
+        let module_order = Arc::new(module_order(&self.modules_vec));
+        let data = Arc::new(self.data_map.clone());
+        let context = Arc::new(ClientContext {
+            data: data.clone(),
+            permissions: Arc::new(self.permissions.clone()),
+            helps: Arc::new(Helps::from_modules(&self.modules_vec)),
+            command_prefixes: self.command_prefixes.clone(),
+            waiters: Arc::new(Waiters::new(&self.dispatch_mode)),
+        });
+        self.module_switches
+            .load()
+            .await
+            .with_context(|| "读取模块开关失败")?;
         Ok(Client {
             rq_client: Arc::new(ricq::Client::new(
                 match &self.device_source {
@@ -672,6 +786,13 @@ impl ClientBuilder {
                 ClientHandler {
                     modules: self.modules_vec.clone(),
                     result_handlers: self.result_handlers_vec.clone(),
+                    middlewares: self.middlewares_vec.clone(),
+                    module_order: module_order.clone(),
+                    dispatch_mode: self.dispatch_mode,
+                    handler_timeout: self.handler_timeout,
+                    queues: Arc::new(ChatQueues::new(&self.dispatch_mode)),
+                    module_switches: self.module_switches.clone(),
+                    context: context.clone(),
                 },
             )),
             authentication: self
@@ -681,6 +802,12 @@ impl ClientBuilder {
             session_store: self.session_store.clone(),
             modules: self.modules_vec.clone(),
             result_handlers: self.result_handlers_vec.clone(),
+            module_order,
+            dispatch_mode: self.dispatch_mode,
+            handler_timeout: self.handler_timeout,
+            module_switches: self.module_switches.clone(),
+            data,
+            context,
             show_qr: if self.show_qr.is_some() {
                 self.show_qr.clone().unwrap()
             } else {
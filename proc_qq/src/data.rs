@@ -0,0 +1,94 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::{CommandPrefixes, Helps, Permissions, Waiters};
+
+/// 通过 ClientBuilder::data 设置的共享数据,
+/// 可以在 #[event] #[result] #[scheduler_job] 中作为参数使用 (db: Data<MyDb>)
+pub struct Data<T: ?Sized>(Arc<T>);
+
+impl<T: Send + Sync + 'static> Data<T> {
+    pub fn new(value: T) -> Self {
+        Data(Arc::new(value))
+    }
+
+    /// 从当前正在处理的事件(或定时任务)中获取共享数据
+    pub fn from_context() -> anyhow::Result<Self> {
+        CONTEXT
+            .try_with(|context| context.data.get::<T>())
+            .ok()
+            .flatten()
+            .ok_or_else(|| anyhow::Error::msg(format!("没有设置共享数据 : {}", type_name::<T>())))
+    }
+}
+
+impl<T: ?Sized> Data<T> {
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T: ?Sized> Clone for Data<T> {
+    fn clone(&self) -> Self {
+        Data(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> Deref for Data<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.as_ref()
+    }
+}
+
+impl<T: ?Sized> From<Arc<T>> for Data<T> {
+    fn from(value: Arc<T>) -> Self {
+        Data(value)
+    }
+}
+
+/// 按照类型存放的共享数据, 每个类型只能有一个
+#[derive(Clone, Default)]
+pub struct DataMap {
+    map: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl DataMap {
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.map.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Data<T>> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.clone().downcast::<T>().ok())
+            .map(Data)
+    }
+}
+
+/// ClientBuilder::build 时确定的客户端设置, 和用户的共享数据分开存放
+pub(crate) struct ClientContext {
+    pub(crate) data: Arc<DataMap>,
+    pub(crate) permissions: Arc<Permissions>,
+    pub(crate) helps: Arc<Helps>,
+    pub(crate) command_prefixes: CommandPrefixes,
+    pub(crate) waiters: Arc<Waiters>,
+}
+
+tokio::task_local! {
+    static CONTEXT: Arc<ClientContext>;
+}
+
+/// 在客户端的作用域中执行, 作用域中可以使用 Data::from_context
+pub(crate) async fn scope_data<F: Future>(context: Arc<ClientContext>, f: F) -> F::Output {
+    CONTEXT.scope(context, f).await
+}
+
+/// 读取当前作用域的客户端设置, 不在作用域中时返回None
+pub(crate) fn with_context<R>(f: impl FnOnce(&ClientContext) -> R) -> Option<R> {
+    CONTEXT.try_with(|context| f(context)).ok()
+}
@@ -4,6 +4,8 @@ use std::sync::Arc;
 
 use tokio_cron_scheduler::Job;
 
+use crate::{scope_data, ClientContext};
+
 pub struct Scheduler {
     pub id: String,
     pub jobs: Vec<SchedulerJob>,
@@ -31,6 +33,7 @@ struct SchedulerJobProcess {
     job_id: String,
     client: Arc<ricq::Client>,
     handler: Arc<Box<dyn SchedulerJobHandler>>,
+    context: Arc<ClientContext>,
 }
 
 impl SchedulerJobProcess {
@@ -39,8 +42,9 @@ impl SchedulerJobProcess {
         let client = Arc::clone(&self.client);
         let scheduler_id = self.scheduler_id.clone();
         let job_id = self.job_id.clone();
+        let context = Arc::clone(&self.context);
         Box::pin(async move {
-            match handler.call(client).await {
+            match scope_data(context, handler.call(client)).await {
                 Ok(_) => {}
                 Err(e) => {
                     tracing::warn!("定时任务执行失败 : {scheduler_id} : {job_id} : {e}");
@@ -54,6 +58,7 @@ pub(crate) async fn put_scheduler(
     js: &mut tokio_cron_scheduler::JobScheduler,
     scs: Arc<Vec<Scheduler>>,
     client: Arc<ricq::Client>,
+    context: Arc<ClientContext>,
 ) -> anyhow::Result<()> {
     for sc in scs.clone().iter() {
         for job in &sc.jobs {
@@ -62,6 +67,7 @@ pub(crate) async fn put_scheduler(
                 job_id: job.id.clone(),
                 client: Arc::clone(&client),
                 handler: job.handler.clone(),
+                context: Arc::clone(&context),
             };
             let lock = match &job.period {
                 SchedulerJobPeriod::Cron(cron) => {
@@ -0,0 +1,555 @@
+use super::event_args::{
+    matcher_get, CommandArg, CommandMatcher, FromCommandMatcher, FromTupleMatcher, TryFromStr,
+    TupleMatcher,
+};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use ricq::msg::elem::{At, RQElem};
+use ricq_core::pb::msg::elem::Elem;
+use ricq_core::pb::msg::TransElem;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+// 时长
+
+/// 解析时长, 例如 1h30m, 90秒, 1天12小时, 500ms, 只有数字时单位为秒.
+/// 和 #[event] 中 timeout、cooldown 的格式相同 (proc_qq_codegen 的 parse_duration_millis)
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    if value.is_empty() {
+        return None;
+    }
+    let mut millis: u64 = 0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let number = rest[..digits].parse::<u64>().ok()?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        millis = millis.checked_add(number.checked_mul(duration_unit(&rest[..unit])?)?)?;
+        rest = &rest[unit..];
+    }
+    Some(Duration::from_millis(millis))
+}
+
+// 单位对应的毫秒数
+fn duration_unit(unit: &str) -> Option<u64> {
+    match unit.to_ascii_lowercase().as_str() {
+        "ms" | "毫秒" => Some(1),
+        "s" | "sec" | "秒" | "秒钟" => Some(1000),
+        "m" | "min" | "分" | "分钟" => Some(60 * 1000),
+        "h" | "时" | "小时" | "个小时" => Some(60 * 60 * 1000),
+        "d" | "天" | "日" => Some(24 * 60 * 60 * 1000),
+        "w" | "周" | "星期" => Some(7 * 24 * 60 * 60 * 1000),
+        _ => None,
+    }
+}
+
+impl TryFromStr for Duration {
+    fn try_from(value: &str) -> Result<Self, anyhow::Error> {
+        parse_duration(value).ok_or_else(|| anyhow::Error::msg(format!("{} 不是有效的时长", value)))
+    }
+}
+
+impl FromCommandMatcher for Duration {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        matcher.take_token_if(parse_duration)
+    }
+}
+
+impl FromTupleMatcher for Duration {
+    fn get(matcher: &mut TupleMatcher) -> Option<Self> {
+        // 较长的单位写在前面, 否则 "10分钟" 只会匹配到 "10分"
+        static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(
+                r"^(?i)(\d+(毫秒|秒钟|秒|分钟|分|个小时|小时|时|天|日|星期|周|sec|min|ms|[smhdw]))+|^\d+",
+            )
+            .expect("proc_qq 的正则错误")
+        });
+        parse_duration(matcher.take_regex(&REGEX)?.as_str())
+    }
+}
+
+impl CommandArg for Duration {}
+
+// 日期时间
+
+/// 解析日期时间, 例如 2023-01-02 12:30, 2023/01/02, 12:30:00,
+/// 只有日期时为当天的0点, 只有时间时为今天 (本地时区)
+pub fn parse_date_time(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+    ] {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date_time);
+        }
+    }
+    if let Some(date) = parse_date(value) {
+        return Some(date.and_time(NaiveTime::MIN));
+    }
+    parse_time(value).map(|time| Local::now().date_naive().and_time(time))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y/%m/%d"]
+        .into_iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    ["%H:%M:%S", "%H:%M"]
+        .into_iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+}
+
+impl TryFromStr for NaiveDateTime {
+    fn try_from(value: &str) -> Result<Self, anyhow::Error> {
+        parse_date_time(value)
+            .ok_or_else(|| anyhow::Error::msg(format!("{} 不是有效的日期时间", value)))
+    }
+}
+
+impl FromCommandMatcher for NaiveDateTime {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        // 日期和时间之间有空白时是两个参数 (2023-01-02 12:30)
+        if let Some(date) = matcher.take_token_if(parse_date) {
+            let time = matcher.take_token_if(parse_time).unwrap_or(NaiveTime::MIN);
+            return Some(date.and_time(time));
+        }
+        matcher.take_token_if(parse_date_time)
+    }
+}
+
+impl FromTupleMatcher for NaiveDateTime {
+    fn get(matcher: &mut TupleMatcher) -> Option<Self> {
+        static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(
+                r"^\d{4}[-/]\d{1,2}[-/]\d{1,2}([ T]\d{1,2}:\d{1,2}(:\d{1,2})?)?|^\d{1,2}:\d{1,2}(:\d{1,2})?",
+            )
+            .expect("proc_qq 的正则错误")
+        });
+        parse_date_time(matcher.take_regex(&REGEX)?.as_str())
+    }
+}
+
+impl CommandArg for NaiveDateTime {}
+
+// 目标用户
+
+/// 指令中的目标用户, 可以at或者直接写QQ号 (/ban @张三, /ban 123456)
+#[derive(Clone, Debug)]
+pub enum Target {
+    At(At),
+    Uin(i64),
+}
+
+impl Target {
+    pub fn uin(&self) -> i64 {
+        match self {
+            Target::At(at) => at.target,
+            Target::Uin(uin) => *uin,
+        }
+    }
+}
+
+// QQ号最少5位, 可以带有@
+fn parse_uin(value: &str) -> Option<i64> {
+    value
+        .strip_prefix('@')
+        .unwrap_or(value)
+        .parse::<i64>()
+        .ok()
+        .filter(|uin| *uin >= 10000)
+}
+
+impl TryFromStr for Target {
+    fn try_from(value: &str) -> Result<Self, anyhow::Error> {
+        parse_uin(value)
+            .map(Target::Uin)
+            .ok_or_else(|| anyhow::Error::msg(format!("{} 不是有效的QQ号", value)))
+    }
+}
+
+impl FromCommandMatcher for Target {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        matcher_get::<At>(matcher)
+            .map(Target::At)
+            .or_else(|| matcher.take_token_if(parse_uin).map(Target::Uin))
+    }
+}
+
+impl FromTupleMatcher for Target {
+    fn get(matcher: &mut TupleMatcher) -> Option<Self> {
+        static REGEX: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^@?\d{5,}").expect("proc_qq 的正则错误"));
+        parse_uin(matcher.take_regex(&REGEX)?.as_str()).map(Target::Uin)
+    }
+}
+
+impl CommandArg for Target {}
+
+// 群文件
+
+/// 群文件消息中的文件, 群文件消息中只有文件, 没有文字, 一般单独使用 (bot_command = "{file}").
+/// 使用 path 和 bus_id 可以获取下载链接
+#[derive(Clone, Debug, Default)]
+pub struct GroupFile {
+    pub name: String,
+    pub size: i64,
+    pub path: String,
+    pub bus_id: i32,
+}
+
+/// 从消息元素中解析群文件, 群文件为 elem_type = 24 的TransElem, 其中是protobuf编码的ObjMsg
+pub fn parse_group_file(elem: &Elem) -> Option<GroupFile> {
+    let value = match elem {
+        Elem::TransElemInfo(TransElem {
+            elem_type: Some(24),
+            elem_value: Some(value),
+            ..
+        }) => value,
+        _ => return None,
+    };
+    // 1字节的标记(1), 2字节的长度, 之后为ObjMsg
+    if value.len() < 3 || value[0] != 1 {
+        return None;
+    }
+    let len = u16::from_be_bytes([value[1], value[2]]) as usize;
+    let obj_msg = value.get(3..3 + len)?;
+    // ObjMsg.msg_content_info(7) => MsgContentInfo.msg_file(2) => MsgFile
+    let content_info = proto_field(obj_msg, 7)?.bytes()?;
+    let msg_file = proto_field(content_info, 2)?.bytes()?;
+    Some(GroupFile {
+        bus_id: proto_field(msg_file, 1)
+            .and_then(|v| v.varint())
+            .unwrap_or_default() as i32,
+        path: String::from_utf8_lossy(proto_field(msg_file, 2)?.bytes()?).to_string(),
+        size: proto_field(msg_file, 3)
+            .and_then(|v| v.varint())
+            .unwrap_or_default() as i64,
+        name: String::from_utf8_lossy(proto_field(msg_file, 4)?.bytes()?).to_string(),
+    })
+}
+
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+impl<'a> ProtoValue<'a> {
+    fn varint(self) -> Option<u64> {
+        match self {
+            ProtoValue::Varint(value) => Some(value),
+            ProtoValue::Bytes(_) => None,
+        }
+    }
+
+    fn bytes(self) -> Option<&'a [u8]> {
+        match self {
+            ProtoValue::Bytes(value) => Some(value),
+            ProtoValue::Varint(_) => None,
+        }
+    }
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *buf.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+// 取出protobuf消息中第一个编号为number的字段, 只需要varint和bytes两种类型
+fn proto_field(buf: &[u8], number: u64) -> Option<ProtoValue<'_>> {
+    let mut pos = 0;
+    while pos < buf.len() {
+        let key = read_varint(buf, &mut pos)?;
+        let value = match key & 0x7 {
+            0 => ProtoValue::Varint(read_varint(buf, &mut pos)?),
+            1 => {
+                pos += 8;
+                continue;
+            }
+            2 => {
+                let len = read_varint(buf, &mut pos)? as usize;
+                let value = buf.get(pos..pos.checked_add(len)?)?;
+                pos += len;
+                ProtoValue::Bytes(value)
+            }
+            5 => {
+                pos += 4;
+                continue;
+            }
+            _ => return None,
+        };
+        if key >> 3 == number {
+            return Some(value);
+        }
+    }
+    None
+}
+
+// 群文件在消息中是RICQ无法识别的元素, push_text会停在群文件上, 与图片一样按照参数的位置取出
+fn take_group_file(matcher: &mut CommandMatcher) -> Option<GroupFile> {
+    if !matcher.matching.is_empty() {
+        return None;
+    }
+    let file = match matcher.elements.get(matcher.idx)? {
+        RQElem::Other(elem) => parse_group_file(elem)?,
+        _ => return None,
+    };
+    matcher.idx += 1;
+    matcher.push_text();
+    Some(file)
+}
+
+impl FromCommandMatcher for GroupFile {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        take_group_file(matcher)
+    }
+}
+
+impl FromCommandMatcher for Option<GroupFile> {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        Some(take_group_file(matcher))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut value: u64) -> Vec<u8> {
+        let mut buf = vec![];
+        while value >= 0x80 {
+            buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        buf.push(value as u8);
+        buf
+    }
+
+    fn varint_field(number: u64, value: u64) -> Vec<u8> {
+        let mut buf = varint(number << 3);
+        buf.extend(varint(value));
+        buf
+    }
+
+    fn bytes_field(number: u64, value: &[u8]) -> Vec<u8> {
+        let mut buf = varint(number << 3 | 2);
+        buf.extend(varint(value.len() as u64));
+        buf.extend_from_slice(value);
+        buf
+    }
+
+    fn msg_file() -> Vec<u8> {
+        let mut buf = varint_field(1, 102);
+        buf.extend(bytes_field(2, b"/abc-123"));
+        buf.extend(varint_field(3, 2048));
+        buf.extend(bytes_field(4, "文件.txt".as_bytes()));
+        buf
+    }
+
+    fn trans_elem(obj_msg: &[u8]) -> Elem {
+        let mut value = vec![1];
+        value.extend((obj_msg.len() as u16).to_be_bytes());
+        value.extend_from_slice(obj_msg);
+        Elem::TransElemInfo(TransElem {
+            elem_type: Some(24),
+            elem_value: Some(value),
+        })
+    }
+
+    fn obj_msg() -> Vec<u8> {
+        let mut buf = varint_field(1, 4);
+        buf.extend(bytes_field(7, &bytes_field(2, &msg_file())));
+        buf
+    }
+
+    #[test]
+    fn parse_group_file_ok() {
+        let file = parse_group_file(&trans_elem(&obj_msg())).unwrap();
+        assert_eq!(file.bus_id, 102);
+        assert_eq!(file.path, "/abc-123");
+        assert_eq!(file.size, 2048);
+        assert_eq!(file.name, "文件.txt");
+    }
+
+    #[test]
+    fn parse_group_file_other_elem_type() {
+        let elem = Elem::TransElemInfo(TransElem {
+            elem_type: Some(1),
+            elem_value: Some(vec![1, 0, 0]),
+        });
+        assert!(parse_group_file(&elem).is_none());
+    }
+
+    #[test]
+    fn parse_group_file_truncated() {
+        let obj_msg = obj_msg();
+        for len in 0..obj_msg.len() {
+            assert!(parse_group_file(&trans_elem(&obj_msg[..len])).is_none());
+        }
+        // 头部的长度大于实际的长度
+        let mut value = vec![1, 0xff, 0xff];
+        value.extend(obj_msg);
+        let elem = Elem::TransElemInfo(TransElem {
+            elem_type: Some(24),
+            elem_value: Some(value),
+        });
+        assert!(parse_group_file(&elem).is_none());
+        for value in [vec![], vec![1], vec![1, 0], vec![0, 0, 0]] {
+            let elem = Elem::TransElemInfo(TransElem {
+                elem_type: Some(24),
+                elem_value: Some(value),
+            });
+            assert!(parse_group_file(&elem).is_none());
+        }
+    }
+
+    #[test]
+    fn read_varint_overlong() {
+        let buf = [0xff; 11];
+        let mut pos = 0;
+        assert_eq!(read_varint(&buf, &mut pos), None);
+        let mut pos = 0;
+        assert_eq!(read_varint(&[0xff, 0xff], &mut pos), None);
+        let mut pos = 0;
+        assert_eq!(read_varint(&varint(u64::MAX), &mut pos), Some(u64::MAX));
+        assert!(proto_field(&buf, 1).is_none());
+    }
+
+    #[test]
+    fn proto_field_length_overflow() {
+        // bytes字段的长度接近usize::MAX
+        let mut buf = varint(2 << 3 | 2);
+        buf.extend(varint(u64::MAX));
+        assert!(proto_field(&buf, 2).is_none());
+    }
+
+    #[test]
+    fn proto_field_wrong_wire_type() {
+        // group (3, 4) 以及不存在的类型 (6, 7)
+        for wire_type in [3, 4, 6, 7] {
+            let buf = varint(7 << 3 | wire_type);
+            assert!(proto_field(&buf, 7).is_none());
+        }
+        // 需要bytes的字段是varint
+        let obj_msg = varint_field(7, 1);
+        assert!(parse_group_file(&trans_elem(&obj_msg)).is_none());
+        // fixed64和fixed32的字段被跳过, 长度不够时不会越界
+        assert!(proto_field(&varint(1 << 3 | 1), 2).is_none());
+        assert!(proto_field(&varint(1 << 3 | 5), 2).is_none());
+        let mut buf = varint(1 << 3 | 5);
+        buf.extend([0; 4]);
+        buf.extend(varint_field(2, 9));
+        assert_eq!(proto_field(&buf, 2).and_then(ProtoValue::varint), Some(9));
+    }
+
+    fn secs(secs: u64) -> Option<Duration> {
+        Some(Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("90"), secs(90));
+        assert_eq!(parse_duration(" 90 "), secs(90));
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1h30m"), secs(90 * 60));
+        assert_eq!(parse_duration("1H30M"), secs(90 * 60));
+        assert_eq!(parse_duration("10分钟"), secs(10 * 60));
+        assert_eq!(parse_duration("10分"), secs(10 * 60));
+        assert_eq!(parse_duration("1天12小时"), secs(36 * 60 * 60));
+        assert_eq!(parse_duration("1w"), secs(7 * 24 * 60 * 60));
+    }
+
+    #[test]
+    fn parse_duration_invalid() {
+        for value in ["", "m", "10x", "1h30", "h1", "-10s", "1.5h"] {
+            assert_eq!(parse_duration(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn parse_duration_overflow() {
+        assert_eq!(parse_duration("99999999999999999999s"), None);
+        assert_eq!(parse_duration(&format!("{}s", u64::MAX / 1000 + 1)), None);
+        assert_eq!(parse_duration(&format!("{}w", u64::MAX)), None);
+        // 相加后溢出
+        let half = u64::MAX / 2 + 1;
+        assert_eq!(parse_duration(&format!("{}ms{}ms", half, half)), None);
+        // 只有数字时按照秒计算, 不会溢出
+        assert_eq!(parse_duration(&u64::MAX.to_string()), secs(u64::MAX));
+    }
+
+    #[test]
+    fn parse_date_time_formats() {
+        let expected = NaiveDate::from_ymd_opt(2023, 1, 2)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap();
+        for value in [
+            "2023-01-02 12:30",
+            "2023-01-02 12:30:00",
+            "2023-01-02T12:30",
+            "2023/01/02 12:30",
+            "2023/1/2 12:30:00",
+        ] {
+            assert_eq!(parse_date_time(value), Some(expected), "{}", value);
+        }
+        let midnight = NaiveDate::from_ymd_opt(2023, 1, 2)
+            .unwrap()
+            .and_time(NaiveTime::MIN);
+        assert_eq!(parse_date_time("2023-01-02"), Some(midnight));
+        assert_eq!(parse_date_time("2023/01/02"), Some(midnight));
+    }
+
+    #[test]
+    fn parse_date_time_only_time() {
+        let before = Local::now().date_naive();
+        let date_time = parse_date_time("12:30").unwrap();
+        let after = Local::now().date_naive();
+        assert_eq!(
+            date_time.time(),
+            NaiveTime::from_hms_opt(12, 30, 0).unwrap()
+        );
+        // 跨过零点时可能是之后的一天
+        assert!(before <= date_time.date() && date_time.date() <= after);
+    }
+
+    #[test]
+    fn parse_date_time_invalid() {
+        for value in [
+            "",
+            "2023-13-01",
+            "2023-02-30",
+            "25:00",
+            "12:30 2023-01-02",
+            "明天",
+        ] {
+            assert_eq!(parse_date_time(value), None, "{}", value);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+/// bot_command的指令前缀, 例如 "!" "." , 设置了前缀时指令需要以前缀或at机器人开头.
+/// 群设置了前缀时使用群的前缀, 否则使用全局的前缀.
+/// 通过 ClientBuilder::command_prefix 和 ClientBuilder::group_command_prefix 设置, build之后不再改变
+#[derive(Clone, Debug, Default)]
+pub struct CommandPrefixes {
+    global: Vec<String>,
+    groups: HashMap<i64, Vec<String>>,
+}
+
+impl CommandPrefixes {
+    /// 添加全局的前缀
+    pub fn add_global<S: Into<String>>(&mut self, prefix: S) {
+        self.global.push(prefix.into());
+    }
+
+    /// 设置全局的前缀, 为空时不需要前缀
+    pub fn set_global(&mut self, prefixes: Vec<String>) {
+        self.global = prefixes;
+    }
+
+    /// 添加群的前缀
+    pub fn add_group<S: Into<String>>(&mut self, group_code: i64, prefix: S) {
+        self.groups
+            .entry(group_code)
+            .or_default()
+            .push(prefix.into());
+    }
+
+    /// 设置群的前缀, 为空时这个群不需要前缀
+    pub fn set_group(&mut self, group_code: i64, prefixes: Vec<String>) {
+        self.groups.insert(group_code, prefixes);
+    }
+
+    /// 删除群的前缀, 之后这个群使用全局的前缀
+    pub fn remove_group(&mut self, group_code: i64) {
+        self.groups.remove(&group_code);
+    }
+
+    /// 会话使用的前缀, 私聊时group_code为None
+    pub fn prefixes(&self, group_code: Option<i64>) -> &[String] {
+        group_code
+            .and_then(|group_code| self.groups.get(&group_code))
+            .unwrap_or(&self.global)
+    }
+}
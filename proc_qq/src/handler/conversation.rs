@@ -0,0 +1,184 @@
+use super::events::MessageEvent;
+use crate::{with_context, DispatchMode, MessageTarget, MessageTargetTrait};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use ricq::handler::QEvent;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// 过滤等待的消息, 返回false时这条消息会正常分发给模块
+pub type MessageFilter = Box<dyn Fn(&MessageEvent) -> bool + Send + Sync>;
+
+struct Waiter {
+    id: u64,
+    filter: Option<MessageFilter>,
+    sender: oneshot::Sender<MessageEvent>,
+}
+
+/// 客户端中正在等待下一条消息的handler, 按照会话存放
+pub(crate) struct Waiters {
+    map: Mutex<HashMap<MessageTarget, Vec<Waiter>>>,
+    next_id: AtomicU64,
+    // 顺序分发时handler等待期间不会处理下一个事件
+    sequential: bool,
+}
+
+impl Waiters {
+    pub(crate) fn new(dispatch_mode: &DispatchMode) -> Self {
+        Self {
+            map: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            sequential: matches!(dispatch_mode, DispatchMode::Sequential),
+        }
+    }
+
+    fn push(&self, target: MessageTarget, filter: Option<MessageFilter>) -> WaiterGuard<'_> {
+        debug_assert!(
+            !self.sequential,
+            "next_message需要 DispatchMode::Concurrent, 顺序分发时等待期间不会收到下一条消息"
+        );
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.map
+            .lock()
+            .unwrap()
+            .entry(target)
+            .or_default()
+            .push(Waiter { id, filter, sender });
+        WaiterGuard {
+            waiters: self,
+            target,
+            id,
+            receiver,
+        }
+    }
+
+    /// 如果有handler在等待这条消息, 将消息交给handler并返回true
+    pub(crate) fn take(&self, event: &QEvent) -> bool {
+        let message = match event {
+            QEvent::GroupMessage(e) => MessageEvent::GroupMessage(e.clone()),
+            QEvent::FriendMessage(e) => MessageEvent::FriendMessage(e.clone()),
+            QEvent::GroupTempMessage(e) => MessageEvent::GroupTempMessage(e.clone()),
+            _ => return false,
+        };
+        let target = message.target();
+        let mut map = self.map.lock().unwrap();
+        let waiters = match map.get_mut(&target) {
+            Some(waiters) => waiters,
+            None => return false,
+        };
+        let position = waiters
+            .iter()
+            .position(|waiter| waiter.filter.as_ref().is_none_or(|filter| filter(&message)));
+        let taken = match position {
+            Some(position) => waiters.remove(position).sender.send(message).is_ok(),
+            None => false,
+        };
+        if waiters.is_empty() {
+            map.remove(&target);
+        }
+        taken
+    }
+
+    fn remove(&self, target: &MessageTarget, id: u64) {
+        let mut map = self.map.lock().unwrap();
+        if let Some(waiters) = map.get_mut(target) {
+            waiters.retain(|waiter| waiter.id != id);
+            if waiters.is_empty() {
+                map.remove(target);
+            }
+        }
+    }
+}
+
+/// 等待同一个会话中同一个人的下一条消息, 等到的消息不会再分发给模块.
+/// 需要 DispatchMode::Concurrent, 顺序分发时handler等待期间不会处理下一个事件;
+/// 只能在handler的作用域中等待, tokio::spawn 出去的任务中会直接返回None
+pub struct NextMessage {
+    waiters: Option<Arc<Waiters>>,
+    target: MessageTarget,
+    timeout: Option<Duration>,
+    filter: Option<MessageFilter>,
+    cancel: Option<BoxFuture<'static, ()>>,
+}
+
+impl NextMessage {
+    pub(crate) fn new(target: MessageTarget) -> Self {
+        Self {
+            waiters: with_context(|context| context.waiters.clone()),
+            target,
+            timeout: None,
+            filter: None,
+            cancel: None,
+        }
+    }
+
+    /// 设置超时时间, 默认一直等待
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 只等待符合条件的消息, 过滤时持有锁, 不要在过滤器中做耗时的操作
+    pub fn filter<F: Fn(&MessageEvent) -> bool + Send + Sync + 'static>(
+        mut self,
+        filter: F,
+    ) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// 这个future完成时取消等待
+    pub fn cancel_on<F: Future<Output = ()> + Send + 'static>(mut self, cancel: F) -> Self {
+        self.cancel = Some(cancel.boxed());
+        self
+    }
+
+    /// 开始等待, 超时或被取消时返回None
+    pub async fn wait(self) -> Option<MessageEvent> {
+        let waiters = match self.waiters {
+            Some(waiters) => waiters,
+            None => {
+                tracing::warn!("不在handler的作用域中, 无法等待下一条消息");
+                return None;
+            }
+        };
+        // 超时、取消或者future被drop时guard会移除等待
+        let mut guard = waiters.push(self.target, self.filter);
+        let timeout = async {
+            match self.timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => futures::future::pending().await,
+            }
+        };
+        let cancel = async {
+            match self.cancel {
+                Some(cancel) => cancel.await,
+                None => futures::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            message = &mut guard.receiver => message.ok(),
+            _ = timeout => guard.receiver.try_recv().ok(),
+            _ = cancel => guard.receiver.try_recv().ok(),
+        }
+    }
+}
+
+struct WaiterGuard<'a> {
+    waiters: &'a Waiters,
+    target: MessageTarget,
+    id: u64,
+    receiver: oneshot::Receiver<MessageEvent>,
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        self.waiters.remove(&self.target, self.id);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{MessageChainParseTrait, MessageSendToSourceTrait, MessageTarget, MessageTargetTrait};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 冷却的范围
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CooldownPer {
+    /// 每个人单独计算
+    User,
+    /// 每个群单独计算, 私聊时按照人计算
+    Group,
+    /// 所有人共用
+    Global,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum CooldownKey {
+    Global,
+    Group(i64),
+    User(i64),
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// 令牌桶限流, 最多积攒burst次, 每经过period恢复一次.
+/// #[event(cooldown = "30s")] 会为每个handler生成一个, #[event_impl] 中为每个实例生成一个
+pub struct Cooldown {
+    period: Duration,
+    burst: u32,
+    per: CooldownPer,
+    reply: Option<&'static str>,
+    buckets: Mutex<BTreeMap<CooldownKey, Bucket>>,
+}
+
+/// 被限流时handler返回的错误, 会转换为 EventResult::Throttled
+#[derive(Debug)]
+pub struct Throttled {
+    /// 还需要等待的时间
+    pub remaining: Duration,
+}
+
+impl Display for Throttled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "冷却中, 还需要等待 {}秒", self.remaining.as_secs() + 1)
+    }
+}
+
+impl std::error::Error for Throttled {}
+
+impl Cooldown {
+    /// reply为被限流时回复的消息, 其中的 {remaining} 会被替换为需要等待的秒数
+    pub const fn new(
+        period: Duration,
+        burst: u32,
+        per: CooldownPer,
+        reply: Option<&'static str>,
+    ) -> Self {
+        Self {
+            period,
+            burst,
+            per,
+            reply,
+            buckets: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// 取得一次调用的机会, 被限流时回复消息并返回Throttled
+    pub async fn acquire<E: MessageTargetTrait + MessageSendToSourceTrait>(
+        &self,
+        event: &E,
+    ) -> anyhow::Result<()> {
+        let key = match (self.per, event.target()) {
+            (CooldownPer::Global, _) => CooldownKey::Global,
+            (CooldownPer::Group, MessageTarget::Group(group_code, _)) => {
+                CooldownKey::Group(group_code)
+            }
+            (_, MessageTarget::Group(_, uin))
+            | (_, MessageTarget::Private(uin))
+            | (_, MessageTarget::GroupTemp(_, uin)) => CooldownKey::User(uin),
+        };
+        if let Err(remaining) = self.take(key) {
+            if let Some(reply) = self.reply {
+                let reply = reply.replace("{remaining}", &(remaining.as_secs() + 1).to_string());
+                event
+                    .send_message_to_source(reply.parse_message_chain())
+                    .await?;
+            }
+            return Err(Throttled { remaining }.into());
+        }
+        Ok(())
+    }
+
+    fn take(&self, key: CooldownKey) -> Result<(), Duration> {
+        self.take_at(key, Instant::now())
+    }
+
+    fn take_at(&self, key: CooldownKey, now: Instant) -> Result<(), Duration> {
+        let period = self.period.as_secs_f64();
+        let burst = self.burst.max(1) as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+        // 桶已经装满的可以直接丢掉, 避免一直增长
+        if buckets.len() > 1024 {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() / period < burst
+            });
+        }
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        bucket.tokens =
+            (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() / period).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) * period))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cooldown(burst: u32) -> Cooldown {
+        Cooldown::new(Duration::from_secs(10), burst, CooldownPer::User, None)
+    }
+
+    #[test]
+    fn take_until_empty() {
+        let cooldown = cooldown(3);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(cooldown.take_at(CooldownKey::User(1), now).is_ok());
+        }
+        assert_eq!(
+            cooldown.take_at(CooldownKey::User(1), now),
+            Err(Duration::from_secs(10))
+        );
+        // 其他人的桶不受影响
+        assert!(cooldown.take_at(CooldownKey::User(2), now).is_ok());
+    }
+
+    #[test]
+    fn refill_after_period() {
+        let cooldown = cooldown(2);
+        let now = Instant::now();
+        assert!(cooldown.take_at(CooldownKey::Global, now).is_ok());
+        assert!(cooldown.take_at(CooldownKey::Global, now).is_ok());
+        // 过了一半的时间, 还需要等待另一半
+        let half = now + Duration::from_secs(5);
+        assert_eq!(
+            cooldown.take_at(CooldownKey::Global, half),
+            Err(Duration::from_secs(5))
+        );
+        // 经过period恢复一次
+        let later = now + Duration::from_secs(10);
+        assert!(cooldown.take_at(CooldownKey::Global, later).is_ok());
+        assert!(cooldown.take_at(CooldownKey::Global, later).is_err());
+    }
+
+    #[test]
+    fn refill_up_to_burst() {
+        let cooldown = cooldown(2);
+        let now = Instant::now();
+        assert!(cooldown.take_at(CooldownKey::Group(1), now).is_ok());
+        assert!(cooldown.take_at(CooldownKey::Group(1), now).is_ok());
+        // 很久之后也最多恢复burst次
+        let later = now + Duration::from_secs(1000);
+        assert!(cooldown.take_at(CooldownKey::Group(1), later).is_ok());
+        assert!(cooldown.take_at(CooldownKey::Group(1), later).is_ok());
+        assert!(cooldown.take_at(CooldownKey::Group(1), later).is_err());
+    }
+
+    #[test]
+    fn burst_zero_allows_once() {
+        let cooldown = cooldown(0);
+        let now = Instant::now();
+        assert!(cooldown.take_at(CooldownKey::User(1), now).is_ok());
+        assert!(cooldown.take_at(CooldownKey::User(1), now).is_err());
+    }
+}
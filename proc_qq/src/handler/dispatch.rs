@@ -0,0 +1,120 @@
+use ricq::handler::QEvent;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Semaphore};
+
+use super::ClientHandler;
+
+/// 事件分发方式
+#[derive(Clone, Copy, Debug, Default)]
+pub enum DispatchMode {
+    /// 按模块顺序依次调用, 有handler返回true或出现异常时停止传递 (默认)
+    #[default]
+    Sequential,
+    /// 同一会话(群/好友/临时会话)的事件按到达顺序处理, 不同会话的事件并发处理.
+    /// 同一事件的各个模块同时调用, 模块内的handler仍然依次调用, 返回true只会停止当前模块.
+    /// workers为同时处理事件的最大数量
+    Concurrent { workers: usize },
+}
+
+/// 事件所属的会话, 同一会话的事件按顺序处理
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ChatKey {
+    System,
+    Group(i64),
+    Friend(i64),
+    // (group_code, uin)
+    GroupTemp(i64, i64),
+}
+
+impl From<&QEvent> for ChatKey {
+    fn from(event: &QEvent) -> Self {
+        match event {
+            QEvent::GroupMessage(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupAudioMessage(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupRequest(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::SelfInvited(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::NewMember(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupMute(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupMessageRecall(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupLeave(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupDisband(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupPoke(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::GroupNameUpdate(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::MemberPermissionChange(e) => ChatKey::Group(e.inner.group_code),
+            QEvent::FriendMessage(e) => ChatKey::Friend(e.inner.from_uin),
+            QEvent::FriendAudioMessage(e) => ChatKey::Friend(e.inner.from_uin),
+            QEvent::FriendMessageRecall(e) => ChatKey::Friend(e.inner.friend_uin),
+            QEvent::FriendPoke(e) => ChatKey::Friend(e.inner.sender),
+            QEvent::NewFriendRequest(e) => ChatKey::Friend(e.inner.req_uin),
+            QEvent::NewFriend(e) => ChatKey::Friend(e.inner.uin),
+            QEvent::DeleteFriend(e) => ChatKey::Friend(e.inner.uin),
+            QEvent::GroupTempMessage(e) => ChatKey::GroupTemp(e.inner.group_code, e.inner.from_uin),
+            QEvent::Login(_)
+            | QEvent::KickedOffline(_)
+            | QEvent::MSFOffline(_)
+            | QEvent::ClientDisconnect(_) => ChatKey::System,
+        }
+    }
+}
+
+/// 每个会话一个队列, 队列中有事件时才会有一个task在消费
+pub(crate) struct ChatQueues {
+    semaphore: Arc<Semaphore>,
+    senders: Mutex<HashMap<ChatKey, mpsc::UnboundedSender<QEvent>>>,
+}
+
+impl ChatQueues {
+    pub(crate) fn new(dispatch_mode: &DispatchMode) -> Self {
+        let workers = match dispatch_mode {
+            DispatchMode::Sequential => 1,
+            DispatchMode::Concurrent { workers } => *workers,
+        };
+        Self {
+            semaphore: Arc::new(Semaphore::new(workers.max(1))),
+            senders: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 将事件放入所属会话的队列, 队列没有在消费时启动一个task
+    pub(crate) fn push(&self, handler: &ClientHandler, event: QEvent) {
+        let key = ChatKey::from(&event);
+        let mut senders = self.senders.lock().unwrap();
+        let event = match senders.get(&key) {
+            Some(sender) => match sender.send(event) {
+                Ok(_) => return,
+                Err(err) => err.0,
+            },
+            None => event,
+        };
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let _ = sender.send(event);
+        senders.insert(key, sender);
+        tokio::spawn(consume(handler.clone(), key, receiver));
+    }
+}
+
+async fn consume(
+    handler: ClientHandler,
+    key: ChatKey,
+    mut receiver: mpsc::UnboundedReceiver<QEvent>,
+) {
+    loop {
+        let event = match receiver.try_recv() {
+            Ok(event) => event,
+            Err(_) => {
+                // 发送事件时会持有锁, 持有锁再确认一次队列为空才能退出, 否则会丢失事件
+                let mut senders = handler.queues.senders.lock().unwrap();
+                match receiver.try_recv() {
+                    Ok(event) => event,
+                    Err(_) => {
+                        senders.remove(&key);
+                        return;
+                    }
+                }
+            }
+        };
+        let _permit = handler.queues.semaphore.clone().acquire_owned().await;
+        handler.dispatch(event).await;
+    }
+}
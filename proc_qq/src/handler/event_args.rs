@@ -1,28 +1,132 @@
-use ricq_core::msg::elem::RQElem;
-
+use regex::Regex;
+use ricq_core::msg::elem::{RQElem, Reply};
+use ricq_core::msg::MessageChain;
+use std::sync::LazyLock;
+
+use ricq::client::event::{
+    DeleteFriendEvent, FriendAudioMessageEvent, FriendMessageRecallEvent, FriendPokeEvent,
+    GroupAudioMessageEvent, GroupDisbandEvent, GroupLeaveEvent, GroupMessageRecallEvent,
+    GroupMuteEvent, GroupNameUpdateEvent, GroupPokeEvent, JoinGroupRequestEvent,
+    MemberPermissionChangeEvent, NewFriendEvent, NewFriendRequestEvent, NewMemberEvent,
+    SelfInvitedEvent,
+};
+
+use super::arg_types::parse_group_file;
 use crate::FriendMessageEvent;
 use crate::GroupMessageEvent;
 use crate::GroupTempMessageEvent;
 use crate::MessageEvent;
-use crate::{ImageElement, MessageContentTrait};
+use crate::{
+    with_context, ClientTrait, ImageElement, MessageChainPointTrait, MessageContentTrait,
+    MessageTarget, MessageTargetTrait,
+};
 
 #[derive(Clone, Debug)]
 pub enum EventArg {
     All(Vec<EventArg>),
     Any(Vec<EventArg>),
     Not(Vec<EventArg>),
-    Regexp(String),
+    /// 编译期检查并生成的静态正则
+    Regexp(&'static Regex),
     Eq(String),
-    TrimRegexp(String),
+    TrimRegexp(&'static Regex),
     TrimEq(String),
+    /// 群号, 群消息和群临时会话, 以及群相关的事件
+    Group(Vec<i64>),
+    /// 发送者或者触发事件的人的QQ号
+    User(Vec<i64>),
+    Chat(Vec<ChatKind>),
+    /// 消息中at了机器人, 参数为机器人的QQ号
+    AtBot(i64),
+    /// 事件的目标是机器人 (被戳、被禁言等), 参数为机器人的QQ号
+    TargetIsBot(i64),
 }
 
-#[derive(Clone)]
-pub enum HandEvent<'a> {
-    MessageEvent(&'a MessageEvent, String),
-    FriendMessageEvent(&'a FriendMessageEvent, String),
-    GroupMessageEvent(&'a GroupMessageEvent, String),
-    GroupTempMessageEvent(&'a GroupTempMessageEvent, String),
+/// 会话的类型, #[event(chat = "group|friend|temp")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChatKind {
+    Group,
+    Friend,
+    Temp,
+}
+
+macro_rules! hand_events {
+    ($($event:ident($e:ident) => $group:expr, $user:expr, $target:expr;)*) => {
+        #[derive(Clone)]
+        pub enum HandEvent<'a> {
+            MessageEvent(&'a MessageEvent, String),
+            FriendMessageEvent(&'a FriendMessageEvent, String),
+            GroupMessageEvent(&'a GroupMessageEvent, String),
+            GroupTempMessageEvent(&'a GroupTempMessageEvent, String),
+            $($event(&'a $event),)*
+        }
+
+        $(
+        impl<'a> From<&'a $event> for HandEvent<'a> {
+            fn from(value: &'a $event) -> Self {
+                Self::$event(value)
+            }
+        }
+        )*
+
+        impl HandEvent<'_> {
+            /// 事件所在的群
+            #[allow(unused_variables)]
+            pub fn group_code(&self) -> Option<i64> {
+                match self {
+                    $(HandEvent::$event($e) => $group,)*
+                    _ => match self.message_target()? {
+                        MessageTarget::Group(group_code, _)
+                        | MessageTarget::GroupTemp(group_code, _) => Some(group_code),
+                        MessageTarget::Private(_) => None,
+                    },
+                }
+            }
+
+            /// 触发事件的人, 消息的发送者
+            #[allow(unused_variables)]
+            pub fn user_uin(&self) -> Option<i64> {
+                match self {
+                    $(HandEvent::$event($e) => $user,)*
+                    _ => match self.message_target()? {
+                        MessageTarget::Group(_, uin)
+                        | MessageTarget::Private(uin)
+                        | MessageTarget::GroupTemp(_, uin) => Some(uin),
+                    },
+                }
+            }
+
+            /// 事件的目标, 例如被戳的人、被禁言的人
+            #[allow(unused_variables)]
+            pub fn target_uin(&self) -> Option<i64> {
+                match self {
+                    $(HandEvent::$event($e) => $target,)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+// 事件 => 群号, 触发事件的人, 事件的目标
+hand_events! {
+    GroupPokeEvent(e) => Some(e.inner.group_code), Some(e.inner.sender), Some(e.inner.receiver);
+    FriendPokeEvent(e) => None, Some(e.inner.sender), Some(e.inner.receiver);
+    NewMemberEvent(e) => Some(e.inner.group_code), Some(e.inner.member_uin), Some(e.inner.member_uin);
+    GroupMuteEvent(e) => Some(e.inner.group_code), Some(e.inner.operator_uin), Some(e.inner.target_uin);
+    GroupLeaveEvent(e) => Some(e.inner.group_code), Some(e.inner.operator_uin.unwrap_or(e.inner.member_uin)), Some(e.inner.member_uin);
+    GroupDisbandEvent(e) => Some(e.inner.group_code), Some(e.inner.operator_uin), None;
+    GroupNameUpdateEvent(e) => Some(e.inner.group_code), Some(e.inner.operator_uin), None;
+    GroupMessageRecallEvent(e) => Some(e.inner.group_code), Some(e.inner.operator_uin), Some(e.inner.author_uin);
+    FriendMessageRecallEvent(e) => None, Some(e.inner.friend_uin), None;
+    MemberPermissionChangeEvent(e) => Some(e.inner.group_code), Some(e.inner.member_uin), Some(e.inner.member_uin);
+    JoinGroupRequestEvent(e) => Some(e.inner.group_code), Some(e.inner.req_uin), None;
+    SelfInvitedEvent(e) => Some(e.inner.group_code), Some(e.inner.invitor_uin), None;
+    NewFriendRequestEvent(e) => None, Some(e.inner.req_uin), None;
+    NewFriendEvent(e) => None, Some(e.inner.uin), None;
+    DeleteFriendEvent(e) => None, Some(e.inner.uin), None;
+    GroupAudioMessageEvent(e) => Some(e.inner.group_code), Some(e.inner.from_uin), None;
+    FriendAudioMessageEvent(e) => None, Some(e.inner.from_uin), None;
 }
 
 impl HandEvent<'_> {
@@ -32,8 +136,30 @@ impl HandEvent<'_> {
             HandEvent::FriendMessageEvent(_, content) => &content,
             HandEvent::GroupMessageEvent(_, content) => &content,
             HandEvent::GroupTempMessageEvent(_, content) => &content,
+            _ => return Err(::anyhow::Error::msg("只有消息类型事件可以匹配消息内容")),
         })
     }
+
+    /// 消息类型事件的会话
+    pub fn message_target(&self) -> Option<MessageTarget> {
+        match self {
+            HandEvent::MessageEvent(event, _) => Some(event.target()),
+            HandEvent::FriendMessageEvent(event, _) => Some(event.target()),
+            HandEvent::GroupMessageEvent(event, _) => Some(event.target()),
+            HandEvent::GroupTempMessageEvent(event, _) => Some(event.target()),
+            _ => None,
+        }
+    }
+
+    pub fn message_chain(&self) -> Option<&MessageChain> {
+        match self {
+            HandEvent::MessageEvent(event, _) => Some(event.message_chain()),
+            HandEvent::FriendMessageEvent(event, _) => Some(event.message_chain()),
+            HandEvent::GroupMessageEvent(event, _) => Some(event.message_chain()),
+            HandEvent::GroupTempMessageEvent(event, _) => Some(event.message_chain()),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> From<&'a MessageEvent> for HandEvent<'a> {
@@ -90,22 +216,54 @@ fn match_event_args_not(args: Vec<EventArg>, event: HandEvent) -> ::anyhow::Resu
     Ok(true)
 }
 
-fn match_event_args_regexp(args: String, event: HandEvent) -> ::anyhow::Result<bool> {
-    Ok(regex::Regex::new(args.as_str())?.is_match(event.content()?.as_str()))
+fn match_event_args_regexp(args: &Regex, event: HandEvent) -> ::anyhow::Result<bool> {
+    Ok(args.is_match(event.content()?.as_str()))
 }
 
 fn match_event_args_eq(args: String, event: HandEvent) -> ::anyhow::Result<bool> {
     Ok(args.eq(event.content()?.as_str()))
 }
 
-fn match_event_args_trim_regexp(args: String, event: HandEvent) -> ::anyhow::Result<bool> {
-    Ok(regex::Regex::new(args.as_str())?.is_match(event.content()?.trim()))
+fn match_event_args_trim_regexp(args: &Regex, event: HandEvent) -> ::anyhow::Result<bool> {
+    Ok(args.is_match(event.content()?.trim()))
 }
 
 fn match_event_args_trim_eq(args: String, event: HandEvent) -> ::anyhow::Result<bool> {
     Ok(args.eq(event.content()?.trim()))
 }
 
+fn match_event_args_group(args: Vec<i64>, event: HandEvent) -> ::anyhow::Result<bool> {
+    Ok(event
+        .group_code()
+        .is_some_and(|group_code| args.contains(&group_code)))
+}
+
+fn match_event_args_user(args: Vec<i64>, event: HandEvent) -> ::anyhow::Result<bool> {
+    Ok(event.user_uin().is_some_and(|uin| args.contains(&uin)))
+}
+
+fn match_event_args_chat(args: Vec<ChatKind>, event: HandEvent) -> ::anyhow::Result<bool> {
+    Ok(match event.message_target() {
+        Some(MessageTarget::Group(_, _)) => args.contains(&ChatKind::Group),
+        Some(MessageTarget::Private(_)) => args.contains(&ChatKind::Friend),
+        Some(MessageTarget::GroupTemp(_, _)) => args.contains(&ChatKind::Temp),
+        None => false,
+    })
+}
+
+fn match_event_args_at_bot(bot_uin: i64, event: HandEvent) -> ::anyhow::Result<bool> {
+    Ok(event.message_chain().is_some_and(|chain| {
+        chain
+            .clone()
+            .into_iter()
+            .any(|element| matches!(element, RQElem::At(at) if at.target == bot_uin))
+    }))
+}
+
+fn match_event_args_target_is_bot(bot_uin: i64, event: HandEvent) -> ::anyhow::Result<bool> {
+    Ok(event.target_uin() == Some(bot_uin))
+}
+
 fn match_event_item(arg: EventArg, event: HandEvent) -> ::anyhow::Result<bool> {
     match arg {
         EventArg::All(v) => match_event_args_all(v, event.clone()),
@@ -115,15 +273,101 @@ fn match_event_item(arg: EventArg, event: HandEvent) -> ::anyhow::Result<bool> {
         EventArg::Eq(v) => match_event_args_eq(v, event.clone()),
         EventArg::TrimRegexp(v) => match_event_args_trim_regexp(v, event.clone()),
         EventArg::TrimEq(v) => match_event_args_trim_eq(v, event.clone()),
+        EventArg::Group(v) => match_event_args_group(v, event.clone()),
+        EventArg::User(v) => match_event_args_user(v, event.clone()),
+        EventArg::Chat(v) => match_event_args_chat(v, event.clone()),
+        EventArg::AtBot(v) => match_event_args_at_bot(v, event.clone()),
+        EventArg::TargetIsBot(v) => match_event_args_target_is_bot(v, event.clone()),
     }
 }
 
 //
 
+/// 从正则的命名分组中获取参数, #[event(regexp = "^禁言(?P<uin>\\d+)$")]
+/// 分组没有匹配时capture为None, 返回None时不调用handler
+pub trait FromRegexCapture: Sized {
+    fn from_capture(capture: Option<&str>) -> Option<Self>;
+}
+
+impl FromRegexCapture for String {
+    fn from_capture(capture: Option<&str>) -> Option<Self> {
+        capture.map(String::from)
+    }
+}
+
+impl<T: FromRegexCapture> FromRegexCapture for Option<T> {
+    fn from_capture(capture: Option<&str>) -> Option<Self> {
+        match capture {
+            None => Some(None),
+            Some(_) => T::from_capture(capture).map(Some),
+        }
+    }
+}
+
+macro_rules! capture_from_str {
+    ($($ty:ty),*) => {
+        $(
+        impl FromRegexCapture for $ty {
+            fn from_capture(capture: Option<&str>) -> Option<Self> {
+                capture?.parse::<$ty>().ok()
+            }
+        }
+        )*
+    };
+}
+
+capture_from_str!(i8, i16, i32, i64, i128, isize);
+capture_from_str!(u8, u16, u32, u64, u128, usize);
+capture_from_str!(f32, f64, bool, char);
+
+// 从matching的开头读取一个参数, 返回去掉引号和转义后的参数以及在matching中占用的长度
+// 参数之间使用空白分隔, 引号内的空白不会分隔参数 (/say "hello world")
+// 双引号和引号外可以使用反斜杠转义下一个字符, 单引号内的内容原样保留
+// 相邻的部分会拼接在一起 (a"b c"d => ab cd), 没有闭合的引号会一直读取到末尾
+fn split_token(matching: &str) -> Option<(String, usize)> {
+    if matching.is_empty() {
+        return None;
+    }
+    let mut token = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = matching.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), _) => token.push(c),
+            (_, '\\') => match chars.next() {
+                Some((_, escaped)) => token.push(escaped),
+                None => token.push(c),
+            },
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, _) if c.is_whitespace() => return Some((token, idx)),
+            _ => token.push(c),
+        }
+    }
+    Some((token, matching.len()))
+}
+
+// split_token的反向操作, 用于把 --time=10m 的值放回matching
+fn quote_token(token: &str) -> String {
+    if token.is_empty() {
+        return "\"\"".to_string();
+    }
+    let mut quoted = String::with_capacity(token.len());
+    for c in token.chars() {
+        if c.is_whitespace() || matches!(c, '"' | '\'' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted
+}
+
 pub struct CommandMatcher {
     pub idx: usize,
     pub elements: Vec<RQElem>,
     pub matching: String,
+    /// 回复的消息, 不在elements中, 被Reply参数取出后为None
+    pub reply: Option<Reply>,
 }
 
 impl CommandMatcher {
@@ -132,11 +376,33 @@ impl CommandMatcher {
             idx: 0,
             elements: value,
             matching: String::new(),
+            reply: None,
         };
         matcher.push_text();
         matcher
     }
 
+    /// 使用消息链创建, 同时取出回复的消息
+    pub fn from_message_chain(chain: &MessageChain) -> CommandMatcher {
+        let reply = chain.reply();
+        let mut elements: Vec<RQElem> = chain.clone().into_iter().collect();
+        // 回复群消息时会自动at被回复的人, 不作为指令的内容
+        if let Some(reply) = &reply {
+            let auto_at = elements
+                .iter()
+                .position(|element| !matches!(element, RQElem::Other(_)))
+                .filter(
+                    |idx| matches!(&elements[*idx], RQElem::At(at) if at.target == reply.sender),
+                );
+            if let Some(idx) = auto_at {
+                elements.remove(idx);
+            }
+        }
+        let mut matcher = CommandMatcher::new(elements);
+        matcher.reply = reply;
+        matcher
+    }
+
     pub fn push_text(&mut self) {
         loop {
             if self.idx >= self.elements.len() {
@@ -148,7 +414,8 @@ impl CommandMatcher {
                     self.matching.push_str(st.content.as_str());
                     self.idx += 1;
                 }
-                RQElem::Other(_) => {
+                // 群文件作为参数, 其他无法识别的元素跳过
+                RQElem::Other(elem) if parse_group_file(elem).is_none() => {
                     self.idx += 1;
                 }
                 _ => break,
@@ -158,16 +425,93 @@ impl CommandMatcher {
         self.matching = build;
     }
 
-    pub fn match_command(&mut self, command_name: &str) -> bool {
-        let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-        let mut sp = sp_regexp.split(self.matching.as_str());
-        if let Some(first) = sp.next() {
-            if command_name.eq(first) {
-                self.matching = self.matching[first.len()..].trim().to_string();
+    /// 查看下一个参数 (已处理引号和转义), 不会消耗
+    pub fn peek_token(&self) -> Option<String> {
+        split_token(self.matching.as_str()).map(|(token, _)| token)
+    }
+
+    /// 取出下一个参数 (已处理引号和转义)
+    pub fn take_token(&mut self) -> Option<String> {
+        self.take_token_if(|token| Some(token.to_string()))
+    }
+
+    /// 下一个参数能被f转换时才取出, 否则不消耗
+    pub fn take_token_if<T>(&mut self, f: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        let (token, len) = split_token(self.matching.as_str())?;
+        let result = f(token.as_str())?;
+        self.matching = self.matching[len..].trim().to_string();
+        Some(result)
+    }
+
+    /// 取出剩下的全部文本, 保留原本的空白和引号, 用于 {text..}
+    pub fn take_rest(&mut self) -> String {
+        std::mem::take(&mut self.matching)
+    }
+
+    /// 取出开头的flag (--silent, -t 10m, --time=10m), 返回flag在flags中的下标
+    /// flags中的每一项为 (名称, 是否带值), 带值的flag只取出名称, 值留给之后的参数读取
+    pub fn take_flag(&mut self, flags: &[(&[&str], bool)]) -> Option<usize> {
+        // 引号中的 "-s" 不作为flag
+        if !self.matching.starts_with('-') {
+            return None;
+        }
+        let (token, len) = split_token(self.matching.as_str())?;
+        let (name, value) = match token.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (token.as_str(), None),
+        };
+        let idx = flags.iter().position(|(names, takes_value)| {
+            names.contains(&name) && (*takes_value || value.is_none())
+        })?;
+        let rest = self.matching[len..].trim();
+        self.matching = match value {
+            Some(value) => format!("{} {}", quote_token(value), rest)
+                .trim()
+                .to_string(),
+            None => rest.to_string(),
+        };
+        Some(idx)
+    }
+
+    /// 去掉开头at机器人的部分以及指令前缀 (ClientBuilder::command_prefix),
+    /// 设置了前缀时, 既没有at机器人也没有前缀的消息返回false
+    pub async fn strip_prefix<E: ClientTrait + MessageTargetTrait>(&mut self, event: &E) -> bool {
+        let mut at_bot = false;
+        if self.matching.is_empty() {
+            if let Some(RQElem::At(at)) = self.elements.get(self.idx) {
+                if at.target == event.bot_uin().await {
+                    at_bot = true;
+                    self.idx += 1;
+                    self.push_text();
+                }
+            }
+        }
+        let group_code = match event.target() {
+            MessageTarget::Group(group_code, _) | MessageTarget::GroupTemp(group_code, _) => {
+                Some(group_code)
+            }
+            MessageTarget::Private(_) => None,
+        };
+        let mut prefixes =
+            with_context(|context| context.command_prefixes.prefixes(group_code).to_vec())
+                .unwrap_or_default();
+        if prefixes.is_empty() {
+            return true;
+        }
+        // 较长的前缀优先, 例如 "!!" 和 "!"
+        prefixes.sort_by_key(|prefix| std::cmp::Reverse(prefix.len()));
+        for prefix in prefixes {
+            if let Some(rest) = self.matching.strip_prefix(prefix.as_str()) {
+                self.matching = rest.trim_start().to_string();
                 return true;
             }
         }
-        return false;
+        at_bot
+    }
+
+    pub fn match_command(&mut self, command_name: &str) -> bool {
+        self.take_token_if(|token| command_name.eq(token).then_some(()))
+            .is_some()
     }
 
     pub fn not_blank(&self) -> bool {
@@ -175,16 +519,7 @@ impl CommandMatcher {
     }
 
     pub fn tuple_matcher(&mut self) -> Option<TupleMatcher> {
-        if !self.matching.is_empty() {
-            let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-            let mut sp = sp_regexp.split(self.matching.as_str());
-            if let Some(first) = sp.next() {
-                let first = first.to_string();
-                self.matching = self.matching[first.len()..].trim().to_string();
-                return Some(TupleMatcher::new(first));
-            }
-        }
-        None
+        self.take_token().map(TupleMatcher::new)
     }
 }
 
@@ -199,116 +534,72 @@ pub fn matcher_get<F: Sized + FromCommandMatcher>(matcher: &mut CommandMatcher)
 
 impl FromCommandMatcher for String {
     fn get(matcher: &mut CommandMatcher) -> Option<Self> {
-        if matcher.matching.is_empty() {
-            return None;
-        }
-        let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-        let mut sp = sp_regexp.split(matcher.matching.as_str());
-        if let Some(first) = sp.next() {
-            let result = Some(first.to_string());
-            matcher.matching = matcher.matching[first.len()..].trim().to_string();
-            return result;
-        }
-        None
+        matcher.take_token()
     }
 }
 
 impl FromCommandMatcher for Option<String> {
     fn get(matcher: &mut CommandMatcher) -> Option<Self> {
-        let mut result = None;
-        if matcher.matching.is_empty() {
-            return Some(result);
-        }
-        let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-        let mut sp = sp_regexp.split(matcher.matching.as_str());
-        if let Some(first) = sp.next() {
-            result = Some(first.to_string());
-            matcher.matching = matcher.matching[first.len()..].trim().to_string();
-        }
-        Some(result)
+        Some(matcher.take_token())
     }
 }
 
 impl FromCommandMatcher for Vec<String> {
     fn get(matcher: &mut CommandMatcher) -> Option<Self> {
-        let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-        let result = sp_regexp
-            .split(matcher.matching.as_str())
-            .map(String::from)
-            .collect();
-        matcher.matching = String::default();
+        let mut result = vec![];
+        while let Some(token) = matcher.take_token() {
+            result.push(token);
+        }
         Some(result)
     }
 }
 
+/// {text..} 参数, 取得剩下的全部文本
+pub trait FromCommandRest: Sized {
+    fn from_rest(rest: String) -> Option<Self>;
+}
+
+#[inline]
+pub fn matcher_get_rest<F: Sized + FromCommandRest>(matcher: &mut CommandMatcher) -> Option<F> {
+    F::from_rest(matcher.take_rest())
+}
+
+impl FromCommandRest for String {
+    fn from_rest(rest: String) -> Option<Self> {
+        if rest.is_empty() {
+            None
+        } else {
+            Some(rest)
+        }
+    }
+}
+
+impl FromCommandRest for Option<String> {
+    fn from_rest(rest: String) -> Option<Self> {
+        Some(String::from_rest(rest))
+    }
+}
+
 macro_rules! command_base_ty_supplier {
     ($ty:ty) => {
         impl FromCommandMatcher for $ty {
             fn get(matcher: &mut CommandMatcher) -> Option<$ty> {
-                if matcher.matching.is_empty() {
-                    return None;
-                }
-                let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-                let mut sp = sp_regexp.split(matcher.matching.as_str());
-                if let Some(first) = sp.next() {
-                    let result = match first.parse::<$ty>() {
-                        Ok(value) => Some(value),
-                        Err(_) => return None,
-                    };
-                    matcher.matching = matcher.matching[first.len()..].trim().to_string();
-                    return result;
-                }
-                None
+                matcher.take_token_if(|token| token.parse::<$ty>().ok())
             }
         }
 
         impl FromCommandMatcher for Option<$ty> {
             fn get(matcher: &mut CommandMatcher) -> Option<Self> {
-                let mut result = None;
-                if matcher.matching.is_empty() {
-                    return Some(result);
-                }
-                let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-                let mut sp = sp_regexp.split(matcher.matching.as_str());
-                if let Some(first) = sp.next() {
-                    match first.parse::<$ty>() {
-                        Ok(value) => {
-                            result = Some(value);
-                            matcher.matching = matcher.matching[first.len()..].trim().to_string();
-                        }
-                        _ => {}
-                    };
-                }
-                return Some(result);
+                Some(matcher.take_token_if(|token| token.parse::<$ty>().ok()))
             }
         }
 
         impl FromCommandMatcher for Vec<$ty> {
             fn get(matcher: &mut CommandMatcher) -> Option<Self> {
                 let mut result = vec![];
-                if matcher.matching.is_empty() {
-                    return Some(result);
-                }
-                let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-                let sp = sp_regexp.split(matcher.matching.as_str());
-                let mut new_matching = vec![];
-                for x in sp {
-                    if !new_matching.is_empty() {
-                        new_matching.push(x);
-                    } else {
-                        match x.parse::<$ty>() {
-                            Ok(value) => result.push(value),
-                            Err(_) => {
-                                if result.is_empty() {
-                                    return Some(result);
-                                } else {
-                                    new_matching.push(x);
-                                }
-                            }
-                        }
-                    }
+                while let Some(value) = matcher.take_token_if(|token| token.parse::<$ty>().ok()) {
+                    result.push(value);
                 }
-                matcher.matching = new_matching.join(" ");
                 Some(result)
             }
         }
@@ -406,6 +697,20 @@ command_rq_element_ty_supplier!(ricq::msg::elem::GroupImage, RQElem::GroupImage)
 command_rq_element_ty_supplier!(ricq::msg::elem::FlashImage, RQElem::FlashImage);
 command_rq_element_ty_supplier!(ricq::msg::elem::VideoFile, RQElem::VideoFile);
 
+// 回复的消息不在消息内容中, 按照参数的位置取出即可
+
+impl FromCommandMatcher for Reply {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        matcher.reply.take()
+    }
+}
+
+impl FromCommandMatcher for Option<Reply> {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        Some(matcher.reply.take())
+    }
+}
+
 impl FromCommandMatcher for ImageElement {
     fn get(matcher: &mut CommandMatcher) -> Option<Self> {
         if !matcher.matching.is_empty() {
@@ -528,6 +833,29 @@ impl TupleMatcher {
         }
         false
     }
+
+    /// 和match_command相同, 但是不区分ASCII大小写
+    pub fn match_command_ignore_case(&mut self, command: &str) -> bool {
+        let matched = self
+            .0
+            .get(..command.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(command));
+        if matched {
+            self.0 = self.0[command.len()..].to_string();
+        }
+        matched
+    }
+
+    /// 取出开头匹配regex的部分, regex需要以 ^ 开头
+    pub fn take_regex(&mut self, regex: &Regex) -> Option<String> {
+        let find = regex.find(self.0.as_str())?;
+        if find.start() != 0 || find.end() == 0 {
+            return None;
+        }
+        let result = find.as_str().to_string();
+        self.0 = self.0[find.end()..].to_string();
+        Some(result)
+    }
 }
 
 pub trait FromTupleMatcher: Sized {
@@ -591,8 +919,9 @@ macro_rules! tuple_base_ty_supplier {
     ($ty:ty, $regexp:expr) => {
         impl FromTupleMatcher for $ty {
             fn get(matcher: &mut TupleMatcher) -> Option<Self> {
-                let regex = regex::Regex::new($regexp).expect("proc_qq 的正则错误");
-                if let Some(find) = regex.find(matcher.0.as_str()) {
+                static REGEX: LazyLock<Regex> =
+                    LazyLock::new(|| Regex::new($regexp).expect("proc_qq 的正则错误"));
+                if let Some(find) = REGEX.find(matcher.0.as_str()) {
                     if find.start() == 0 {
                         let parse = matcher.0.as_str()[find.start()..find.end()].to_string();
                         matcher.0 = matcher.0.as_str()[find.end()..].to_string();
@@ -688,27 +1017,60 @@ enum_try_from_str!(u8, u16, u32, u64, u128, usize);
 enum_try_from_str!(f32, f64);
 enum_try_from_str!(bool);
 
+/// #[derive(CommandArg)] 生成的标记, 使 Option<T> 和 Vec<T> 也可以作为参数
+pub trait CommandArg: FromCommandMatcher {}
+
+impl<T: CommandArg> FromCommandMatcher for Option<T> {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        Some(T::get(matcher))
+    }
+}
+
+impl<T: CommandArg> FromCommandMatcher for Vec<T> {
+    fn get(matcher: &mut CommandMatcher) -> Option<Self> {
+        let mut result = vec![];
+        loop {
+            let before = (matcher.idx, matcher.matching.len());
+            match T::get(matcher) {
+                Some(value) => result.push(value),
+                None => break,
+            }
+            // 没有消耗任何内容时停止, 避免一直循环
+            if before == (matcher.idx, matcher.matching.len()) {
+                break;
+            }
+        }
+        Some(result)
+    }
+}
+
+impl<T: CommandArg + FromTupleMatcher> FromTupleMatcher for Option<T> {
+    fn get(matcher: &mut TupleMatcher) -> Option<Self> {
+        Some(tuple_matcher_get::<T>(matcher))
+    }
+}
+
+impl<T: CommandArg + FromTupleMatcher> FromTupleMatcher for Vec<T> {
+    fn get(matcher: &mut TupleMatcher) -> Option<Self> {
+        Some(match tuple_matcher_get::<T>(matcher) {
+            None => vec![],
+            Some(value) => vec![value],
+        })
+    }
+}
+
 #[inline]
 pub fn matcher_get_enum<'a, F: Sized + TryFromStr>(
     matcher: &mut CommandMatcher,
     values: Vec<&str>,
 ) -> Option<F> {
-    if matcher.matching.is_empty() {
-        return None;
-    }
-    let sp_regexp = regex::Regex::new("\\s+").expect("proc_qq 正则错误");
-    let mut sp = sp_regexp.split(matcher.matching.as_str());
-    if let Some(first) = sp.next() {
-        if values.contains(&first) {
-            let result = match F::try_from(first) {
-                Ok(v) => Some(v),
-                Err(_) => return None,
-            };
-            matcher.matching = matcher.matching[first.len()..].trim().to_string();
-            return result;
+    matcher.take_token_if(|token| {
+        if values.contains(&token) {
+            F::try_from(token).ok()
+        } else {
+            None
         }
-    }
-    None
+    })
 }
 
 #[inline]
@@ -732,3 +1094,123 @@ pub fn tuple_matcher_get_enum<'a, F: Sized + TryFromStr>(
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(matching: &str) -> (String, usize) {
+        split_token(matching).unwrap()
+    }
+
+    #[test]
+    fn split_token_whitespace() {
+        assert_eq!(split_token(""), None);
+        assert_eq!(token("ban 10"), ("ban".to_string(), 3));
+        assert_eq!(token("ban"), ("ban".to_string(), 3));
+    }
+
+    #[test]
+    fn split_token_quotes() {
+        assert_eq!(token("\"hello world\" x"), ("hello world".to_string(), 13));
+        assert_eq!(token("'a\\b c' x"), ("a\\b c".to_string(), 7));
+        assert_eq!(token("a\"b c\"d e"), ("ab cd".to_string(), 7));
+        assert_eq!(token("\"\" x"), (String::new(), 2));
+        assert_eq!(token("\"it's\""), ("it's".to_string(), 6));
+    }
+
+    #[test]
+    fn split_token_escape() {
+        assert_eq!(token("a\\ b c"), ("a b".to_string(), 4));
+        assert_eq!(token("\"a\\\"b\""), ("a\"b".to_string(), 6));
+        assert_eq!(token("\\'a"), ("'a".to_string(), 3));
+    }
+
+    #[test]
+    fn split_token_unterminated_quote() {
+        assert_eq!(token("\"hello world"), ("hello world".to_string(), 12));
+        assert_eq!(token("'hello world"), ("hello world".to_string(), 12));
+        assert_eq!(token("a\"b c"), ("ab c".to_string(), 5));
+    }
+
+    #[test]
+    fn split_token_trailing_backslash() {
+        assert_eq!(token("abc\\"), ("abc\\".to_string(), 4));
+        assert_eq!(token("\"abc\\"), ("abc\\".to_string(), 5));
+        assert_eq!(token("'abc\\"), ("abc\\".to_string(), 5));
+    }
+
+    #[test]
+    fn quote_token_round_trip() {
+        for value in [
+            "", "10m", "a b", "a\"b", "it's", "\\", "a\\ b", "中 文", "a\tb",
+        ] {
+            let quoted = quote_token(value);
+            assert_eq!(
+                token(&quoted),
+                (value.to_string(), quoted.len()),
+                "{}",
+                value
+            );
+        }
+    }
+
+    fn matcher(matching: &str) -> CommandMatcher {
+        CommandMatcher {
+            idx: 0,
+            elements: vec![],
+            matching: matching.to_string(),
+            reply: None,
+        }
+    }
+
+    const FLAGS: &[(&[&str], bool)] = &[(&["--silent", "-s"], false), (&["--time", "-t"], true)];
+
+    #[test]
+    fn take_flag_without_value() {
+        let mut m = matcher("-s rest");
+        assert_eq!(m.take_flag(FLAGS), Some(0));
+        assert_eq!(m.matching, "rest");
+        // 不带值的flag不能使用 =
+        let mut m = matcher("--silent=1 rest");
+        assert_eq!(m.take_flag(FLAGS), None);
+        assert_eq!(m.matching, "--silent=1 rest");
+    }
+
+    #[test]
+    fn take_flag_with_value() {
+        let mut m = matcher("-t 10m rest");
+        assert_eq!(m.take_flag(FLAGS), Some(1));
+        assert_eq!(m.take_token().as_deref(), Some("10m"));
+        assert_eq!(m.matching, "rest");
+        let mut m = matcher("--time=10m rest");
+        assert_eq!(m.take_flag(FLAGS), Some(1));
+        assert_eq!(m.take_token().as_deref(), Some("10m"));
+        assert_eq!(m.matching, "rest");
+        let mut m = matcher("--time=\"1 h\" rest");
+        assert_eq!(m.take_flag(FLAGS), Some(1));
+        assert_eq!(m.take_token().as_deref(), Some("1 h"));
+        assert_eq!(m.matching, "rest");
+    }
+
+    #[test]
+    fn take_flag_empty_value() {
+        let mut m = matcher("--time= rest");
+        assert_eq!(m.take_flag(FLAGS), Some(1));
+        assert_eq!(m.take_token().as_deref(), Some(""));
+        assert_eq!(m.matching, "rest");
+        let mut m = matcher("--time=");
+        assert_eq!(m.take_flag(FLAGS), Some(1));
+        assert_eq!(m.take_token().as_deref(), Some(""));
+        assert_eq!(m.take_token(), None);
+    }
+
+    #[test]
+    fn take_flag_not_flag() {
+        for matching in ["\"-s\" rest", "--unknown rest", "rest -s", ""] {
+            let mut m = matcher(matching);
+            assert_eq!(m.take_flag(FLAGS), None, "{}", matching);
+            assert_eq!(m.matching, matching);
+        }
+    }
+}
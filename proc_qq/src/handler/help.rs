@@ -0,0 +1,238 @@
+use super::permission::Require;
+use super::processes::{MessageEventProcess, ModuleEventHandler, ModuleEventProcess};
+use super::Module;
+use crate::{
+    with_context, MessageChainParseTrait, MessageContentTrait, MessageEvent,
+    MessageSendToSourceTrait,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// handler的帮助信息, #[event] 根据bot_command(或usage)、eq、文档注释和require生成
+#[derive(Clone, Debug, Default)]
+pub struct HandlerHelp {
+    /// 指令, 例如 "/ban|/禁言 {user} [{time}]"
+    pub commands: Vec<String>,
+    /// handler的文档注释
+    pub description: String,
+    /// 需要的权限
+    pub requires: Vec<Require>,
+}
+
+impl HandlerHelp {
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.description.is_empty()
+    }
+
+    // 指令的第一个词 (包括别名) 是否为keyword
+    fn has_keyword(&self, keyword: &str) -> bool {
+        self.commands.iter().any(|command| {
+            command
+                .split_whitespace()
+                .next()
+                .is_some_and(|first| first.split('|').any(|name| name == keyword))
+        })
+    }
+
+    // 一行的概要 "/ban {user} : 禁言 (需要 群管理员)"
+    fn render_line(&self) -> String {
+        let mut line = self.commands.join(" / ");
+        // 概要只使用说明的第一行
+        if let Some(first) = self.description.lines().next() {
+            if !line.is_empty() {
+                line.push_str(" : ");
+            }
+            line.push_str(first);
+        }
+        if !self.requires.is_empty() {
+            line.push_str(&format!(" (需要 {})", self.render_requires()));
+        }
+        line
+    }
+
+    fn render_requires(&self) -> String {
+        self.requires
+            .iter()
+            .map(Require::description)
+            .collect::<Vec<_>>()
+            .join("、")
+    }
+}
+
+/// 模块的帮助信息
+#[derive(Clone, Debug)]
+pub struct ModuleHelp {
+    pub id: String,
+    pub name: String,
+    /// module!(.., description = "...")
+    pub description: String,
+    /// (handler的名称, 帮助信息), 不包括没有帮助信息的handler
+    pub handlers: Vec<(String, HandlerHelp)>,
+}
+
+impl Module {
+    pub fn help(&self) -> ModuleHelp {
+        let mut handlers: Vec<(String, HandlerHelp)> = vec![];
+        for handle in &self.handles {
+            let help = &handle.help;
+            // 同一个指令在不同聊天中分开处理时, 没有说明的只显示一次
+            let repeated = help.description.is_empty()
+                && help.commands.iter().all(|command| {
+                    handlers
+                        .iter()
+                        .any(|(_, other)| other.commands.contains(command))
+                });
+            if !help.is_empty() && !repeated {
+                handlers.push((handle.name.clone(), help.clone()));
+            }
+        }
+        ModuleHelp {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            handlers,
+        }
+    }
+}
+
+impl ModuleHelp {
+    /// 模块和其中的指令
+    pub fn render(&self) -> String {
+        let mut result = self.name.clone();
+        if !self.description.is_empty() {
+            result.push_str(&format!(" : {}", self.description));
+        }
+        for (idx, (_, help)) in self.handlers.iter().enumerate() {
+            let branch = if idx + 1 == self.handlers.len() {
+                "└"
+            } else {
+                "├"
+            };
+            result.push_str(&format!("\n {} {}", branch, help.render_line()));
+        }
+        result
+    }
+}
+
+/// 所有模块的帮助信息, ClientBuilder::build 时根据模块生成,
+/// handler中可以使用 Helps::from_context 获取
+#[derive(Clone, Debug, Default)]
+pub struct Helps {
+    /// 不包括名称为空的模块
+    pub modules: Vec<ModuleHelp>,
+}
+
+impl Helps {
+    /// 从当前正在处理的事件(或定时任务)中获取全部模块的帮助信息
+    pub fn from_context() -> anyhow::Result<Arc<Self>> {
+        with_context(|context| context.helps.clone())
+            .ok_or_else(|| anyhow::Error::msg("不在客户端的作用域中"))
+    }
+
+    pub fn from_modules(modules: &[Module]) -> Self {
+        Helps {
+            modules: modules
+                .iter()
+                .filter(|module| !module.name.is_empty())
+                .map(Module::help)
+                .collect(),
+        }
+    }
+
+    /// 按照模块名称或id查找
+    pub fn find_module(&self, name: &str) -> Option<&ModuleHelp> {
+        self.modules
+            .iter()
+            .find(|module| module.name == name || module.id == name)
+    }
+
+    /// 按照指令的第一个词查找, 例如 "/ban"
+    pub fn find_command(&self, keyword: &str) -> Option<(&ModuleHelp, &HandlerHelp)> {
+        self.modules.iter().find_map(|module| {
+            module
+                .handlers
+                .iter()
+                .find(|(_, help)| help.has_keyword(keyword))
+                .map(|(_, help)| (module, help))
+        })
+    }
+
+    /// 所有的模块和指令
+    pub fn render(&self) -> String {
+        self.modules
+            .iter()
+            .map(|module| format!("❤️ {}", module.render()))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// 单个指令的详细说明
+    pub fn render_command(module: &ModuleHelp, help: &HandlerHelp) -> String {
+        let mut result = vec![format!("指令 : {}", help.commands.join(" / "))];
+        if !help.description.is_empty() {
+            result.push(format!("说明 : {}", help.description));
+        }
+        if !help.requires.is_empty() {
+            result.push(format!("权限 : {}", help.render_requires()));
+        }
+        result.push(format!("模块 : {}", module.name));
+        result.join("\n")
+    }
+}
+
+/// 内置的帮助模块, 发送 "{name}" 查看全部模块和指令,
+/// "{name} 模块名" 查看模块, "{name} 指令" 查看指令的详细说明
+pub fn help_module<S: Into<String>>(id: S, name: S) -> Module {
+    let name = name.into();
+    Module {
+        id: id.into(),
+        name: name.clone(),
+        description: "查看模块和指令的说明".to_owned(),
+        priority: 0,
+        handles: vec![ModuleEventHandler {
+            name: "help".to_owned(),
+            priority: 0,
+            timeout: None,
+            help: HandlerHelp {
+                commands: vec![format!("{} [模块名|指令]", name)],
+                description: "查看模块和指令的说明".to_owned(),
+                requires: vec![],
+            },
+            process: ModuleEventProcess::Message(Box::new(HelpHandler { keyword: name })),
+        }],
+    }
+}
+
+struct HelpHandler {
+    keyword: String,
+}
+
+#[async_trait]
+impl MessageEventProcess for HelpHandler {
+    async fn handle(&self, event: &MessageEvent) -> anyhow::Result<bool> {
+        let content = event.message_content();
+        let arg = match content.trim().strip_prefix(self.keyword.as_str()) {
+            Some(arg) if arg.is_empty() || arg.starts_with(char::is_whitespace) => arg.trim(),
+            _ => return Ok(false),
+        };
+        let helps = Helps::from_context()?;
+        let reply = if arg.is_empty() {
+            format!(
+                "{}\n\n发送 \"{} 模块名\" 或 \"{} 指令\" 查看详细说明",
+                helps.render(),
+                self.keyword,
+                self.keyword
+            )
+        } else if let Some(module) = helps.find_module(arg) {
+            module.render()
+        } else if let Some((module, help)) = helps.find_command(arg) {
+            Helps::render_command(module, help)
+        } else {
+            format!("没有找到模块或指令 : {}", arg)
+        };
+        event
+            .send_message_to_source(reply.parse_message_chain())
+            .await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,34 @@
+use super::results::EventResult;
+use async_trait::async_trait;
+use ricq::handler::QEvent;
+
+/// 中间件, 在模块处理事件的前后调用, 用于日志、鉴权、黑名单、统计、改写消息等.
+/// 只作用于RICQ的事件, 不作用于ConnectedAndOnline和DisconnectedAndOffline.
+/// 每个事件调用一次before和一次after, 不是每个handler调用一次
+#[async_trait]
+pub trait Middleware: Sync + Send {
+    /// 事件分发给模块之前调用, 按照注册的顺序调用, 可以修改事件.
+    /// 在对话(next_message)取出回复之前调用, 被拦截的消息也不会作为对话的回复.
+    /// 并发分发时也在排队之前依次调用, 不要在这里做耗时的操作
+    /// 返回值为 anyhow::Result<bool>, Ok(true)为拦截事件, 不再调用之后的中间件和模块.
+    /// 返回Err时同样不再分发, 结果处理器会收到 EventResult::MiddlewareException
+    async fn before(&self, _event: &mut QEvent) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// 事件处理完成之后调用, 每个事件只调用一次 (包括被拦截的事件), 按照注册的顺序调用
+    async fn after(&self, _event: &QEvent, _outcome: &EventOutcome) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// 一个事件的处理结果, 传给中间件的after
+pub enum EventOutcome {
+    /// 被中间件拦截 (before返回Ok(true)), 没有分发给模块
+    Intercepted,
+    /// 作为对话(next_message)的回复被取出, 没有分发给模块
+    Replied,
+    /// 产生的事件结果, 与结果处理器收到的相同, 为空时没有handler处理这个事件.
+    /// 中间件的before返回Err时为 EventResult::MiddlewareException
+    Results(Vec<EventResult>),
+}
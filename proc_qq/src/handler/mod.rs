@@ -1,25 +1,59 @@
+use crate::{scope_data, ClientContext};
+#[cfg(feature = "event_args")]
+pub use arg_types::*;
 use async_trait::async_trait;
+pub use command_prefix::*;
+pub use conversation::*;
+pub use cooldown::*;
+pub use dispatch::*;
 #[cfg(feature = "event_args")]
 pub use event_args::*;
 pub use events::*;
+use futures::FutureExt;
+pub use help::*;
+pub use middleware::*;
+pub use permission::*;
 pub use processes::*;
 pub use results::*;
 use ricq::handler::{Handler, QEvent};
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use std::time::Duration;
+pub use switches::*;
+pub use usage::*;
 
+#[cfg(feature = "event_args")]
+mod arg_types;
+mod command_prefix;
+mod conversation;
+mod cooldown;
+mod dispatch;
 #[cfg(feature = "event_args")]
 mod event_args;
 mod events;
+mod help;
+mod middleware;
+mod permission;
 mod processes;
 mod results;
+mod switches;
+mod usage;
 
+#[derive(Clone)]
 pub(crate) struct ClientHandler {
     pub(crate) modules: Arc<Vec<Module>>,
     pub(crate) result_handlers: Arc<Vec<EventResultHandler>>,
+    pub(crate) middlewares: Arc<Vec<Box<dyn Middleware>>>,
+    pub(crate) module_order: Arc<ModuleOrder>,
+    pub(crate) dispatch_mode: DispatchMode,
+    pub(crate) handler_timeout: Option<Duration>,
+    pub(crate) queues: Arc<ChatQueues>,
+    pub(crate) module_switches: Arc<ModuleSwitches>,
+    pub(crate) context: Arc<ClientContext>,
 }
 
-impl ClientHandler {}
-
 enum MapResult<'a> {
     None,
     Process(&'a str, &'a str),
@@ -27,11 +61,12 @@ enum MapResult<'a> {
 }
 
 macro_rules! map_result {
-    ($self:expr, $event:expr, $result_handler:path, $event_result:expr) => {
+    ($self:expr, $results:expr, $event:expr, $result_handler:path, $event_result:expr) => {
+        let event_result = $event_result;
         for h in $self.result_handlers.as_ref() {
             let mut hand = false;
             match &h.process {
-                $result_handler(e) => match e.handle($event, $event_result).await {
+                $result_handler(e) => match e.handle($event, &event_result).await {
                     Ok(b) => {
                         hand = b;
                     }
@@ -40,7 +75,7 @@ macro_rules! map_result {
                         hand = true;
                     }
                 },
-                ResultProcess::OnlyResult(e) => match e.handle($event_result).await {
+                ResultProcess::OnlyResult(e) => match e.handle(&event_result).await {
                     Ok(b) => {
                         hand = b;
                     }
@@ -55,51 +90,148 @@ macro_rules! map_result {
                 break;
             }
         }
+        // 注册了中间件时收集事件结果, 分发完成后传给中间件的after
+        if let Some(results) = $results {
+            results.lock().unwrap().push(event_result);
+        }
     };
 }
 
-macro_rules! map_handlers {
-    ($self:expr $(,$event:expr, $process:path, $result_handler:path)* $(,)?) => {{
+/// 调用handler, 超时时取消handler并返回None, handler发生panic时转换为错误
+async fn call_handler<F: Future<Output = anyhow::Result<bool>>>(
+    timeout: Option<Duration>,
+    handle: F,
+) -> Option<anyhow::Result<bool>> {
+    let handle = AssertUnwindSafe(handle).catch_unwind();
+    let result = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, handle).await.ok()?,
+        None => handle.await,
+    };
+    Some(match result {
+        Ok(result) => result,
+        Err(panic) => Err(anyhow::Error::msg(format!(
+            "handler panicked : {}",
+            panic_message(&*panic)
+        ))),
+    })
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "unknown"
+    }
+}
+
+macro_rules! map_module {
+    ($self:expr, $target:expr, $results:expr, $m:expr, $handles:expr $(,$event:ident, $process:path, $result_handler:path)*) => {{
+        let m: &Module = $m;
+        let results: Option<&std::sync::Mutex<Vec<EventResult>>> = $results;
+        let target: &SwitchTarget = $target;
+        let enabled = $self.module_switches.is_enabled(&m.id, target.group_code, target.uin);
         let mut result = MapResult::None;
-        for m in $self.modules.as_ref() {
-            for h in &m.handles {
-                match &h.process {
-                    $(
-                    $process(e) => match e.handle($event).await {
-                        Ok(b) => {
-                            if b {
-                                result = MapResult::Process(&m.id, &h.name);
-                                let event_result = EventResult::Process(
-                                    ModuleInfo{
-                                        module_id: m.id.clone(),
-                                        module_name: m.name.clone(),
-                                        handle_name: h.name.clone(),
-                                    },
-                                );
-                                map_result!($self, $event, $result_handler, &event_result);
-                            }
-                        }
-                        Err(err) => {
-                            tracing::error!(" 出现错误 : {:?}", err);
-                            result = MapResult::Exception(&m.id, &h.name);
-                            let event_result = EventResult::Exception(
+        for hi in $handles {
+            let h = &m.handles[*hi];
+            match &h.process {
+                $(
+                $process(_) if !enabled => {
+                    let event_result = EventResult::Skipped(
+                        ModuleInfo{
+                            module_id: m.id.clone(),
+                            module_name: m.name.clone(),
+                            handle_name: h.name.clone(),
+                        },
+                    );
+                    map_result!($self, results, $event, $result_handler, event_result);
+                }
+                $process(e) => match call_handler(h.timeout.or($self.handler_timeout), e.handle($event)).await {
+                    Some(Ok(b)) => {
+                        if b {
+                            result = MapResult::Process(&m.id, &h.name);
+                            let event_result = EventResult::Process(
                                 ModuleInfo{
                                     module_id: m.id.clone(),
                                     module_name: m.name.clone(),
                                     handle_name: h.name.clone(),
                                 },
-                                err,
                             );
-                            map_result!($self, $event, $result_handler, &event_result);
+                            map_result!($self, results, $event, $result_handler, event_result);
                         }
-                    },
-                    )*
-                    _ => (),
-                }
-                if let MapResult::None = result {
-                } else {
-                    break;
-                }
+                    }
+                    Some(Err(err)) if err.is::<Throttled>() => {
+                        // 被限流视为已经处理, 不再向下传递
+                        result = MapResult::Process(&m.id, &h.name);
+                        let remaining = err.downcast_ref::<Throttled>().unwrap().remaining;
+                        let event_result = EventResult::Throttled(
+                            ModuleInfo{
+                                module_id: m.id.clone(),
+                                module_name: m.name.clone(),
+                                handle_name: h.name.clone(),
+                            },
+                            remaining,
+                        );
+                        map_result!($self, results, $event, $result_handler, event_result);
+                    }
+                    Some(Err(err)) if err.is::<PermissionDenied>() => {
+                        // 没有权限同样视为已经处理
+                        result = MapResult::Process(&m.id, &h.name);
+                        let require = err.downcast_ref::<PermissionDenied>().unwrap().require;
+                        let event_result = EventResult::Denied(
+                            ModuleInfo{
+                                module_id: m.id.clone(),
+                                module_name: m.name.clone(),
+                                handle_name: h.name.clone(),
+                            },
+                            require,
+                        );
+                        map_result!($self, results, $event, $result_handler, event_result);
+                    }
+                    Some(Err(err)) if err.is::<CommandParseError>() => {
+                        // 已经回复了用法, 同样视为已经处理
+                        result = MapResult::Process(&m.id, &h.name);
+                        let error = err.downcast::<CommandParseError>().unwrap();
+                        let event_result = EventResult::CommandParseError(
+                            ModuleInfo{
+                                module_id: m.id.clone(),
+                                module_name: m.name.clone(),
+                                handle_name: h.name.clone(),
+                            },
+                            error,
+                        );
+                        map_result!($self, results, $event, $result_handler, event_result);
+                    }
+                    Some(Err(err)) => {
+                        tracing::error!(" 出现错误 : {:?}", err);
+                        result = MapResult::Exception(&m.id, &h.name);
+                        let event_result = EventResult::Exception(
+                            ModuleInfo{
+                                module_id: m.id.clone(),
+                                module_name: m.name.clone(),
+                                handle_name: h.name.clone(),
+                            },
+                            err,
+                        );
+                        map_result!($self, results, $event, $result_handler, event_result);
+                    }
+                    None => {
+                        // 超时和异常一样不再向下传递
+                        tracing::error!(" 处理超时 : {} : {}", m.id, h.name);
+                        result = MapResult::Exception(&m.id, &h.name);
+                        let event_result = EventResult::Timeout(
+                            ModuleInfo{
+                                module_id: m.id.clone(),
+                                module_name: m.name.clone(),
+                                handle_name: h.name.clone(),
+                            },
+                        );
+                        map_result!($self, results, $event, $result_handler, event_result);
+                    }
+                },
+                )*
+                _ => (),
             }
             if let MapResult::None = result {
             } else {
@@ -110,15 +242,153 @@ macro_rules! map_handlers {
     }};
 }
 
+macro_rules! map_handlers {
+    ($self:expr, $target:expr, $results:expr, $exception:expr $(,$event:ident, $process:path, $result_handler:path)* $(,)?) => {{
+        let target: &SwitchTarget = $target;
+        let results: Option<&std::sync::Mutex<Vec<EventResult>>> = $results;
+        let exception: Option<anyhow::Error> = $exception;
+        $(let $event = &$event;)*
+        match (exception, $self.dispatch_mode) {
+            // 中间件返回了错误, 不分发给模块, 只交给结果处理器
+            (Some(err), _) => {
+                let event_result = EventResult::MiddlewareException(err);
+                for h in $self.result_handlers.as_ref() {
+                    let hand = match &h.process {
+                        $($result_handler(e) => e.handle($event, &event_result).await,)*
+                        ResultProcess::OnlyResult(e) => e.handle(&event_result).await,
+                        _ => continue,
+                    };
+                    match hand {
+                        Ok(false) => (),
+                        Ok(true) => break,
+                        Err(err) => {
+                            tracing::error!(" 出现错误 : {:?}", err);
+                            break;
+                        }
+                    }
+                }
+                if let Some(results) = results {
+                    results.lock().unwrap().push(event_result);
+                }
+                MapResult::None
+            }
+            (None, DispatchMode::Sequential) => {
+                let mut result = MapResult::None;
+                for (mi, hs) in $self.module_order.iter() {
+                    let m = &$self.modules[*mi];
+                    result = map_module!($self, target, results, m, hs $(,$event, $process, $result_handler)*);
+                    if let MapResult::None = result {
+                    } else {
+                        break;
+                    }
+                }
+                result
+            }
+            (None, DispatchMode::Concurrent { .. }) => {
+                // 模块之间互不影响, 有异常时优先返回异常
+                let results = futures::future::join_all($self.module_order.iter().map(|(mi, hs)| async move {
+                    let m = &$self.modules[*mi];
+                    map_module!($self, target, results, m, hs $(,$event, $process, $result_handler)*)
+                }))
+                .await;
+                let mut result = MapResult::None;
+                for r in results {
+                    match r {
+                        MapResult::None => (),
+                        MapResult::Process(_, _) => {
+                            if let MapResult::None = result {
+                                result = r;
+                            }
+                        }
+                        MapResult::Exception(_, _) => {
+                            result = r;
+                            break;
+                        }
+                    }
+                }
+                result
+            }
+        }
+    }};
+}
+
 #[async_trait]
 impl Handler for ClientHandler {
-    async fn handle(&self, e: QEvent) {
+    async fn handle(&self, mut e: QEvent) {
+        update_member_permission(&e);
+        // 中间件最先调用, 被拦截的事件也不会作为对话(next_message)的回复
+        match scope_data(self.context.clone(), self.before(&mut e)).await {
+            Ok(false) => (),
+            Ok(true) => {
+                self.after(&e, &EventOutcome::Intercepted).await;
+                return;
+            }
+            Err(err) => {
+                tracing::error!(" 出现错误 : {:?}", err);
+                scope_data(self.context.clone(), self.map_event(e, Some(err))).await;
+                return;
+            }
+        }
+        // 有handler在等待的消息不再分发, 需要在排队之前取出, 否则同一会话的队列会一直等待
+        if self.context.waiters.take(&e) {
+            self.after(&e, &EventOutcome::Replied).await;
+            return;
+        }
+        match self.dispatch_mode {
+            DispatchMode::Sequential => self.dispatch(e).await,
+            DispatchMode::Concurrent { .. } => self.queues.push(self, e),
+        }
+    }
+}
+
+impl ClientHandler {
+    pub(crate) async fn dispatch(&self, e: QEvent) {
+        scope_data(self.context.clone(), self.map_event(e, None)).await
+    }
+
+    // 按照注册的顺序调用中间件的before, 返回Ok(true)时拦截事件
+    async fn before(&self, e: &mut QEvent) -> anyhow::Result<bool> {
+        for mw in self.middlewares.iter() {
+            if mw.before(e).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // 每个事件调用一次中间件的after
+    async fn after(&self, e: &QEvent, outcome: &EventOutcome) {
+        let after = async {
+            for mw in self.middlewares.iter() {
+                if let Err(err) = mw.after(e, outcome).await {
+                    tracing::error!(" 出现错误 : {:?}", err);
+                }
+            }
+        };
+        scope_data(self.context.clone(), after).await
+    }
+
+    // exception为中间件before返回的错误, 不为None时只交给结果处理器
+    async fn map_event(&self, e: QEvent, exception: Option<anyhow::Error>) {
+        let target = &SwitchTarget::from(&e);
+        // 只有注册了中间件时才需要保留原始事件和收集事件结果
+        let qevent = if self.middlewares.is_empty() {
+            None
+        } else {
+            Some(e.clone())
+        };
+        let results = std::sync::Mutex::new(vec![]);
+        let results = qevent.as_ref().map(|_| &results);
         match e {
             QEvent::Login(event) => {
                 tracing::debug!("LOGIN : (UIN={})", event,);
+                let event = LoginEvent { uin: event };
                 let _ = map_handlers!(
-                    &self,
-                    &LoginEvent { uin: event },
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::LoginEvent,
                     ResultProcess::LoginEvent,
                 );
@@ -132,11 +402,14 @@ impl Handler for ClientHandler {
                 );
                 let me = MessageEvent::GroupMessage(event.clone());
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupMessage,
                     ResultProcess::GroupMessage,
-                    &me,
+                    me,
                     ModuleEventProcess::Message,
                     ResultProcess::Message,
                 );
@@ -149,11 +422,14 @@ impl Handler for ClientHandler {
                 );
                 let me = MessageEvent::FriendMessage(event.clone());
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::FriendMessage,
                     ResultProcess::FriendMessage,
-                    &me,
+                    me,
                     ModuleEventProcess::Message,
                     ResultProcess::Message,
                 );
@@ -166,11 +442,14 @@ impl Handler for ClientHandler {
                 );
                 let me = MessageEvent::GroupTempMessage(event.clone());
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupTempMessage,
                     ResultProcess::GroupTempMessage,
-                    &me,
+                    me,
                     ModuleEventProcess::Message,
                     ResultProcess::Message,
                 );
@@ -183,8 +462,11 @@ impl Handler for ClientHandler {
                     event.inner.message,
                 );
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::JoinGroupRequest,
                     ResultProcess::JoinGroupRequest,
                 );
@@ -196,176 +478,273 @@ impl Handler for ClientHandler {
                     event.inner.message
                 );
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::NewFriendRequest,
                     ResultProcess::NewFriendRequest,
                 );
             }
             QEvent::NewFriend(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::NewFriend,
                     ResultProcess::NewFriend
                 );
             }
             QEvent::FriendPoke(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::FriendPoke,
                     ResultProcess::FriendPoke
                 );
             }
             QEvent::DeleteFriend(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::DeleteFriend,
                     ResultProcess::DeleteFriend
                 );
             }
             QEvent::GroupMute(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupMute,
                     ResultProcess::GroupMute
                 );
             }
             QEvent::GroupLeave(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupLeave,
                     ResultProcess::GroupLeave
                 );
             }
             QEvent::GroupNameUpdate(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupNameUpdate,
                     ResultProcess::GroupNameUpdate
                 );
             }
             QEvent::GroupMessageRecall(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupMessageRecall,
                     ResultProcess::GroupMessageRecall
                 );
             }
             QEvent::FriendMessageRecall(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::FriendMessageRecall,
                     ResultProcess::FriendMessageRecall
                 );
             }
             QEvent::MSFOffline(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::MSFOffline,
                     ResultProcess::MSFOffline
                 );
             }
             QEvent::KickedOffline(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::KickedOffline,
                     ResultProcess::KickedOffline
                 );
             }
             QEvent::GroupDisband(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupDisband,
                     ResultProcess::GroupDisband
                 );
             }
             QEvent::MemberPermissionChange(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::MemberPermissionChange,
                     ResultProcess::MemberPermissionChange
                 );
             }
             QEvent::SelfInvited(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::SelfInvited,
                     ResultProcess::SelfInvited
                 );
             }
             QEvent::GroupAudioMessage(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupAudioMessage,
                     ResultProcess::GroupAudioMessage
                 );
             }
             QEvent::FriendAudioMessage(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::FriendAudioMessage,
                     ResultProcess::FriendAudioMessage
                 );
             }
             QEvent::NewMember(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::NewMember,
                     ResultProcess::NewMember
                 );
             }
             QEvent::ClientDisconnect(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::ClientDisconnect,
                     ResultProcess::ClientDisconnect
                 );
             }
             QEvent::GroupPoke(event) => {
                 let _ = map_handlers!(
-                    &self,
-                    &event,
+                    self,
+                    target,
+                    results,
+                    exception,
+                    event,
                     ModuleEventProcess::GroupPoke,
                     ResultProcess::GroupPoke
                 );
             }
         }
+        if let (Some(qevent), Some(results)) = (qevent, results) {
+            let results = std::mem::take(&mut *results.lock().unwrap());
+            self.after(&qevent, &EventOutcome::Results(results)).await;
+        }
     }
 }
 
 pub struct Module {
     pub id: String,
     pub name: String,
+    /// 模块的说明, 用于生成帮助 module!(.., description = "...")
+    pub description: String,
+    /// 优先级, 数值越大越先调用, 相同时按照声明的顺序
+    pub priority: i32,
     pub handles: Vec<ModuleEventHandler>,
 }
 
+/// 按优先级排序后的模块下标, 以及模块中handler的下标
+pub(crate) type ModuleOrder = Vec<(usize, Vec<usize>)>;
+
+pub(crate) fn module_order(modules: &[Module]) -> ModuleOrder {
+    let mut module_idx: Vec<usize> = (0..modules.len()).collect();
+    module_idx.sort_by_key(|i| std::cmp::Reverse(modules[*i].priority));
+    module_idx
+        .into_iter()
+        .map(|mi| {
+            let handles = &modules[mi].handles;
+            let mut handle_idx: Vec<usize> = (0..handles.len()).collect();
+            handle_idx.sort_by_key(|i| std::cmp::Reverse(handles[*i].priority));
+            (mi, handle_idx)
+        })
+        .collect()
+}
+
 pub(crate) struct EventSender {
     pub(crate) modules: Arc<Vec<Module>>,
     pub(crate) result_handlers: Arc<Vec<EventResultHandler>>,
+    pub(crate) module_order: Arc<ModuleOrder>,
+    pub(crate) dispatch_mode: DispatchMode,
+    pub(crate) handler_timeout: Option<Duration>,
+    pub(crate) module_switches: Arc<ModuleSwitches>,
+    pub(crate) context: Arc<ClientContext>,
 }
 
 impl EventSender {
     pub async fn send_connected_and_online(&self) -> anyhow::Result<()> {
+        scope_data(self.context.clone(), self.map_connected_and_online()).await
+    }
+    pub async fn send_disconnected_and_offline(&self) -> anyhow::Result<()> {
+        scope_data(self.context.clone(), self.map_disconnected_and_offline()).await
+    }
+    async fn map_connected_and_online(&self) -> anyhow::Result<()> {
+        let event = ConnectedAndOnlineEvent {};
         match map_handlers!(
-            &self,
-            &ConnectedAndOnlineEvent {},
+            self,
+            &SwitchTarget::default(),
+            None,
+            None,
+            event,
             ModuleEventProcess::ConnectedAndOnline,
             ResultProcess::ConnectedAndOnline,
         ) {
@@ -373,10 +752,14 @@ impl EventSender {
             _ => Ok(()),
         }
     }
-    pub async fn send_disconnected_and_offline(&self) -> anyhow::Result<()> {
+    async fn map_disconnected_and_offline(&self) -> anyhow::Result<()> {
+        let event = DisconnectedAndOfflineEvent {};
         match map_handlers!(
-            &self,
-            &DisconnectedAndOfflineEvent {},
+            self,
+            &SwitchTarget::default(),
+            None,
+            None,
+            event,
             ModuleEventProcess::DisconnectedAndOffline,
             ResultProcess::DisconnectedAndOffline,
         ) {
@@ -0,0 +1,182 @@
+use crate::{
+    with_context, MemberPermissionTrait, MessageChainParseTrait, MessageSendToSourceTrait,
+    MessageTarget, MessageTargetTrait,
+};
+use ricq::handler::QEvent;
+use ricq_core::structs::GroupMemberPermission;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 调用handler需要的权限, #[event(require = "group_admin")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Require {
+    /// 发送者是群主或管理员, 超级用户也视为满足
+    GroupAdmin,
+    /// 发送者是群主, 超级用户也视为满足
+    GroupOwner,
+    /// 机器人是群主或管理员 (禁言、踢人等操作需要)
+    BotAdmin,
+    /// 发送者在 ClientBuilder::superusers 中
+    Superuser,
+}
+
+impl Require {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Require::GroupAdmin => "群管理员",
+            Require::GroupOwner => "群主",
+            Require::BotAdmin => "机器人为群管理员",
+            Require::Superuser => "超级用户",
+        }
+    }
+}
+
+/// 权限设置, 通过 ClientBuilder::superusers 和 ClientBuilder::permission_denied_reply 设置,
+/// handler中可以使用 Permissions::from_context 获取
+#[derive(Clone, Debug, Default)]
+pub struct Permissions {
+    /// 超级用户的QQ号
+    pub superusers: Vec<i64>,
+    /// 没有权限时回复的消息, 其中的 {require} 会被替换为需要的权限, handler上的require_reply优先
+    pub denied_reply: Option<String>,
+}
+
+impl Permissions {
+    /// 从当前正在处理的事件(或定时任务)中获取客户端的权限设置
+    pub fn from_context() -> anyhow::Result<Arc<Self>> {
+        with_context(|context| context.permissions.clone())
+            .ok_or_else(|| anyhow::Error::msg("不在客户端的作用域中"))
+    }
+
+    pub fn is_superuser(&self, uin: i64) -> bool {
+        self.superusers.contains(&uin)
+    }
+}
+
+/// 没有权限时handler返回的错误, 会转换为 EventResult::Denied
+#[derive(Debug)]
+pub struct PermissionDenied {
+    /// 没有满足的权限
+    pub require: Require,
+}
+
+impl Display for PermissionDenied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "没有权限, 需要 : {}", self.require.description())
+    }
+}
+
+impl std::error::Error for PermissionDenied {}
+
+/// 检查事件的发送者是否满足所有的权限, 不满足时回复消息并返回PermissionDenied
+pub async fn check_require<
+    E: MessageTargetTrait + MessageSendToSourceTrait + MemberPermissionTrait,
+>(
+    event: &E,
+    requires: &[Require],
+    reply: Option<&'static str>,
+) -> anyhow::Result<()> {
+    let permissions = Permissions::from_context().ok();
+    for require in requires {
+        if has_require(event, *require, permissions.as_deref()).await? {
+            continue;
+        }
+        let reply = reply.or(permissions
+            .as_ref()
+            .and_then(|permissions| permissions.denied_reply.as_deref()));
+        if let Some(reply) = reply {
+            let reply = reply.replace("{require}", require.description());
+            event
+                .send_message_to_source(reply.parse_message_chain())
+                .await?;
+        }
+        return Err(PermissionDenied { require: *require }.into());
+    }
+    Ok(())
+}
+
+async fn has_require<E: MessageTargetTrait + MessageSendToSourceTrait + MemberPermissionTrait>(
+    event: &E,
+    require: Require,
+    permissions: Option<&Permissions>,
+) -> anyhow::Result<bool> {
+    let (group_code, uin) = match event.target() {
+        MessageTarget::Group(group_code, uin) | MessageTarget::GroupTemp(group_code, uin) => {
+            (Some(group_code), uin)
+        }
+        MessageTarget::Private(uin) => (None, uin),
+    };
+    let is_superuser = permissions.is_some_and(|permissions| permissions.is_superuser(uin));
+    Ok(match (require, group_code) {
+        (Require::Superuser, _) => is_superuser,
+        (Require::GroupAdmin, _) | (Require::GroupOwner, _) if is_superuser => true,
+        // 私聊中没有群权限
+        (_, None) => false,
+        (Require::GroupAdmin, Some(group_code)) => !matches!(
+            event.get_member_permission(group_code, uin).await?,
+            GroupMemberPermission::Member
+        ),
+        (Require::GroupOwner, Some(group_code)) => matches!(
+            event.get_member_permission(group_code, uin).await?,
+            GroupMemberPermission::Owner
+        ),
+        (Require::BotAdmin, Some(group_code)) => {
+            let bot_uin = event.bot_uin().await;
+            !matches!(
+                event.get_member_permission(group_code, bot_uin).await?,
+                GroupMemberPermission::Member
+            )
+        }
+    })
+}
+
+// 群成员权限的缓存, 收到MemberPermissionChange时更新
+const MEMBER_PERMISSION_TTL: Duration = Duration::from_secs(5 * 60);
+
+type MemberKey = (usize, i64, i64);
+
+static MEMBER_PERMISSIONS: Mutex<BTreeMap<MemberKey, (GroupMemberPermission, Instant)>> =
+    Mutex::new(BTreeMap::new());
+
+pub(crate) fn cached_member_permission(
+    client: &ricq::Client,
+    group_code: i64,
+    uin: i64,
+) -> Option<GroupMemberPermission> {
+    let key = (client as *const ricq::Client as usize, group_code, uin);
+    MEMBER_PERMISSIONS
+        .lock()
+        .unwrap()
+        .get(&key)
+        .filter(|(_, cached)| cached.elapsed() < MEMBER_PERMISSION_TTL)
+        .map(|(permission, _)| permission.clone())
+}
+
+pub(crate) fn cache_member_permission(
+    client: &ricq::Client,
+    group_code: i64,
+    uin: i64,
+    permission: GroupMemberPermission,
+) {
+    let key = (client as *const ricq::Client as usize, group_code, uin);
+    let mut permissions = MEMBER_PERMISSIONS.lock().unwrap();
+    // 过期的缓存直接丢掉, 避免一直增长
+    if permissions.len() > 4096 {
+        permissions.retain(|_, (_, cached)| cached.elapsed() < MEMBER_PERMISSION_TTL);
+    }
+    permissions.insert(key, (permission, Instant::now()));
+}
+
+/// 群成员权限变化时更新缓存
+pub(crate) fn update_member_permission(event: &QEvent) {
+    if let QEvent::MemberPermissionChange(e) = event {
+        cache_member_permission(
+            &e.client,
+            e.inner.group_code,
+            e.inner.member_uin,
+            e.inner.new_permission.clone(),
+        );
+    }
+}
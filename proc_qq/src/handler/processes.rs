@@ -1,4 +1,5 @@
 use super::events::*;
+use super::help::HandlerHelp;
 use async_trait::async_trait;
 use ricq::client::event::{
     ClientDisconnect, DeleteFriendEvent, FriendAudioMessageEvent, FriendMessageEvent,
@@ -8,12 +9,42 @@ use ricq::client::event::{
     MemberPermissionChangeEvent, NewFriendEvent, NewFriendRequestEvent, NewMemberEvent,
     SelfInvitedEvent,
 };
+use std::sync::Arc;
 
 pub struct ModuleEventHandler {
     pub name: String,
+    /// 优先级, 数值越大越先调用, 相同时按照声明的顺序
+    pub priority: i32,
+    /// 超时时间, 为None时使用客户端的默认超时时间
+    pub timeout: Option<std::time::Duration>,
+    /// 帮助信息, 由 #[event] 生成
+    pub help: HandlerHelp,
     pub process: ModuleEventProcess,
 }
 
+/// 由 #[event_impl] 生成, 结构体中的每个 #[event] 方法对应一个handler, handler共享结构体的实例
+pub trait EventHandlers: Send + Sync + 'static {
+    fn handlers(self: Arc<Self>) -> Vec<ModuleEventHandler>;
+}
+
+/// module! 的参数, #[event] 生成的结构体 (以及其他可以转换为ModuleEventHandler的类型),
+/// #[event_impl] 结构体的实例或者Arc
+pub trait IntoModuleEventHandlers {
+    fn into_handlers(self) -> Vec<ModuleEventHandler>;
+}
+
+impl<T: Into<ModuleEventHandler>> IntoModuleEventHandlers for T {
+    fn into_handlers(self) -> Vec<ModuleEventHandler> {
+        vec![self.into()]
+    }
+}
+
+impl<T: EventHandlers> IntoModuleEventHandlers for Arc<T> {
+    fn into_handlers(self) -> Vec<ModuleEventHandler> {
+        self.handlers()
+    }
+}
+
 pub enum ModuleEventProcess {
     GroupMessage(Box<dyn GroupMessageEventProcess>),
     FriendMessage(Box<dyn FriendMessageEventProcess>),
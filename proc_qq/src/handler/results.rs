@@ -1,4 +1,6 @@
 use super::events::*;
+use super::permission::Require;
+use super::usage::CommandParseError;
 use async_trait::async_trait;
 use ricq::client::event::{
     ClientDisconnect, DeleteFriendEvent, FriendAudioMessageEvent, FriendMessageEvent,
@@ -18,6 +20,18 @@ pub struct ModuleInfo {
 pub enum EventResult {
     Process(ModuleInfo),
     Exception(ModuleInfo, anyhow::Error),
+    /// 模块在这个群/QQ号被关闭, handler没有被调用
+    Skipped(ModuleInfo),
+    /// handler超过超时时间没有返回, 已经被取消
+    Timeout(ModuleInfo),
+    /// 调用太频繁被限流 (#[event(cooldown = "30s")]), 以及还需要等待的时间
+    Throttled(ModuleInfo, std::time::Duration),
+    /// 没有满足handler需要的权限 (#[event(require = "group_admin")])
+    Denied(ModuleInfo, Require),
+    /// 指令的关键字匹配但是参数不正确, 已经回复了用法 (#[event(bot_command = "..", usage = true)])
+    CommandParseError(ModuleInfo, CommandParseError),
+    /// 中间件的before返回了错误, 事件没有分发给模块
+    MiddlewareException(anyhow::Error),
 }
 
 pub struct EventResultHandler {
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use ricq::handler::QEvent;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// 模块开关的作用范围
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SwitchScope {
+    /// 所有的群和好友
+    Global,
+    /// 某个群 (group_code)
+    Group(i64),
+    /// 某个QQ号 (uin), 包括好友消息、临时会话以及群里这个人触发的事件
+    User(i64),
+}
+
+/// 模块开关的存储方式, 与SessionStore一样只存取字节
+#[async_trait]
+pub trait ModuleSwitchStore {
+    async fn save_switches(&self, data: Vec<u8>) -> Result<()>;
+    async fn load_switches(&self) -> Result<Option<Vec<u8>>>;
+}
+
+pub struct FileModuleSwitchStore {
+    pub path: String,
+}
+
+impl FileModuleSwitchStore {
+    pub fn boxed(path: impl Into<String>) -> Box<dyn ModuleSwitchStore + Send + Sync> {
+        Box::new(Self { path: path.into() })
+    }
+}
+
+#[async_trait]
+impl ModuleSwitchStore for FileModuleSwitchStore {
+    async fn save_switches(&self, data: Vec<u8>) -> Result<()> {
+        tokio::fs::write(self.path.as_str(), data).await?;
+        Ok(())
+    }
+    async fn load_switches(&self) -> Result<Option<Vec<u8>>> {
+        if Path::new(self.path.as_str()).exists() {
+            Ok(Some(tokio::fs::read(self.path.as_str()).await?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct ModuleSwitch {
+    global: Option<bool>,
+    groups: HashMap<i64, bool>,
+    users: HashMap<i64, bool>,
+}
+
+/// 模块开关, 没有设置过的模块默认开启.
+/// 范围越小越优先 : 用户 > 群 > 全局
+pub struct ModuleSwitches {
+    store: Option<Box<dyn ModuleSwitchStore + Sync + Send>>,
+    switches: RwLock<HashMap<String, ModuleSwitch>>,
+    // 修改开关时持有, 保证写入Store和内存的顺序一致
+    set_lock: tokio::sync::Mutex<()>,
+}
+
+impl ModuleSwitches {
+    pub fn new(store: Option<Box<dyn ModuleSwitchStore + Sync + Send>>) -> Self {
+        Self {
+            store,
+            switches: RwLock::new(HashMap::new()),
+            set_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// 从Store中读取开关
+    pub async fn load(&self) -> Result<()> {
+        if let Some(store) = &self.store {
+            if let Some(data) = store.load_switches().await? {
+                *self.switches.write().unwrap() = serde_json::from_slice(&data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 将开关写入Store
+    pub async fn save(&self) -> Result<()> {
+        let data = serde_json::to_vec(&*self.switches.read().unwrap())?;
+        self.save_data(data).await
+    }
+
+    async fn save_data(&self, data: Vec<u8>) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save_switches(data).await?;
+        }
+        Ok(())
+    }
+
+    /// 开启模块
+    pub async fn enable(&self, module_id: &str, scope: SwitchScope) -> Result<()> {
+        self.set(module_id, scope, Some(true)).await
+    }
+
+    /// 关闭模块
+    pub async fn disable(&self, module_id: &str, scope: SwitchScope) -> Result<()> {
+        self.set(module_id, scope, Some(false)).await
+    }
+
+    /// 清除这个范围的设置, 使用更大范围的设置
+    pub async fn reset(&self, module_id: &str, scope: SwitchScope) -> Result<()> {
+        self.set(module_id, scope, None).await
+    }
+
+    // 先在副本上修改并写入Store, 写入成功后才替换内存中的开关, 失败时内存中的开关不变
+    async fn set(&self, module_id: &str, scope: SwitchScope, value: Option<bool>) -> Result<()> {
+        let _guard = self.set_lock.lock().await;
+        let mut switches = self.switches.read().unwrap().clone();
+        let switch = switches.entry(module_id.to_owned()).or_default();
+        match scope {
+            SwitchScope::Global => switch.global = value,
+            SwitchScope::Group(group_code) => set_or_remove(&mut switch.groups, group_code, value),
+            SwitchScope::User(uin) => set_or_remove(&mut switch.users, uin, value),
+        }
+        self.save_data(serde_json::to_vec(&switches)?).await?;
+        *self.switches.write().unwrap() = switches;
+        Ok(())
+    }
+
+    /// 模块在这个群/QQ号是否开启
+    pub fn is_enabled(&self, module_id: &str, group_code: Option<i64>, uin: Option<i64>) -> bool {
+        let switches = self.switches.read().unwrap();
+        let switch = match switches.get(module_id) {
+            Some(switch) => switch,
+            None => return true,
+        };
+        if let Some(enabled) = uin.and_then(|uin| switch.users.get(&uin)) {
+            return *enabled;
+        }
+        if let Some(enabled) = group_code.and_then(|group_code| switch.groups.get(&group_code)) {
+            return *enabled;
+        }
+        switch.global.unwrap_or(true)
+    }
+}
+
+impl Default for ModuleSwitches {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+fn set_or_remove(map: &mut HashMap<i64, bool>, key: i64, value: Option<bool>) {
+    match value {
+        Some(value) => {
+            map.insert(key, value);
+        }
+        None => {
+            map.remove(&key);
+        }
+    }
+}
+
+/// 事件发生的群和触发事件的QQ号, 用于判断模块是否开启
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct SwitchTarget {
+    pub(crate) group_code: Option<i64>,
+    pub(crate) uin: Option<i64>,
+}
+
+impl SwitchTarget {
+    fn group(group_code: i64, uin: i64) -> Self {
+        Self {
+            group_code: Some(group_code),
+            uin: Some(uin),
+        }
+    }
+
+    fn user(uin: i64) -> Self {
+        Self {
+            group_code: None,
+            uin: Some(uin),
+        }
+    }
+}
+
+impl From<&QEvent> for SwitchTarget {
+    fn from(event: &QEvent) -> Self {
+        match event {
+            QEvent::GroupMessage(e) => Self::group(e.inner.group_code, e.inner.from_uin),
+            QEvent::GroupAudioMessage(e) => Self::group(e.inner.group_code, e.inner.from_uin),
+            QEvent::GroupRequest(e) => Self::group(e.inner.group_code, e.inner.req_uin),
+            QEvent::SelfInvited(e) => Self::group(e.inner.group_code, e.inner.invitor_uin),
+            QEvent::NewMember(e) => Self::group(e.inner.group_code, e.inner.member_uin),
+            QEvent::GroupMute(e) => Self::group(e.inner.group_code, e.inner.operator_uin),
+            QEvent::GroupMessageRecall(e) => Self::group(e.inner.group_code, e.inner.operator_uin),
+            QEvent::GroupLeave(e) => Self::group(e.inner.group_code, e.inner.member_uin),
+            QEvent::GroupDisband(e) => Self::group(e.inner.group_code, e.inner.operator_uin),
+            QEvent::GroupPoke(e) => Self::group(e.inner.group_code, e.inner.sender),
+            QEvent::GroupNameUpdate(e) => Self::group(e.inner.group_code, e.inner.operator_uin),
+            QEvent::MemberPermissionChange(e) => {
+                Self::group(e.inner.group_code, e.inner.member_uin)
+            }
+            QEvent::GroupTempMessage(e) => Self::group(e.inner.group_code, e.inner.from_uin),
+            QEvent::FriendMessage(e) => Self::user(e.inner.from_uin),
+            QEvent::FriendAudioMessage(e) => Self::user(e.inner.from_uin),
+            QEvent::FriendMessageRecall(e) => Self::user(e.inner.friend_uin),
+            QEvent::FriendPoke(e) => Self::user(e.inner.sender),
+            QEvent::NewFriendRequest(e) => Self::user(e.inner.req_uin),
+            QEvent::NewFriend(e) => Self::user(e.inner.uin),
+            QEvent::DeleteFriend(e) => Self::user(e.inner.uin),
+            QEvent::Login(_)
+            | QEvent::KickedOffline(_)
+            | QEvent::MSFOffline(_)
+            | QEvent::ClientDisconnect(_) => Self::default(),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{MessageChainParseTrait, MessageContentTrait, MessageSendToSourceTrait};
+use std::fmt::{Display, Formatter};
+
+/// 指令的关键字匹配但是参数不正确时handler返回的错误, 会转换为 EventResult::CommandParseError
+/// #[event(bot_command = "/ban {user} {time}", usage = true)]
+#[derive(Debug)]
+pub struct CommandParseError {
+    /// 指令的用法, 默认为bot_command
+    pub usage: &'static str,
+    /// 收到的消息
+    pub content: String,
+}
+
+impl Display for CommandParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "指令参数不正确 : {} , 用法 : {}",
+            self.content, self.usage
+        )
+    }
+}
+
+impl std::error::Error for CommandParseError {}
+
+/// 回复指令的用法并返回CommandParseError, reply中的 {usage} 会被替换为用法
+pub async fn reply_usage<E: MessageContentTrait + MessageSendToSourceTrait>(
+    event: &E,
+    usage: &'static str,
+    reply: &'static str,
+) -> anyhow::Result<bool> {
+    event
+        .send_message_to_source(reply.replace("{usage}", usage).parse_message_chain())
+        .await?;
+    Err(CommandParseError {
+        usage,
+        content: event.message_content(),
+    }
+    .into())
+}
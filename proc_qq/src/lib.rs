@@ -1,6 +1,7 @@
 #![feature(impl_trait_in_assoc_type)]
 
 pub use client::*;
+pub use data::*;
 pub use entities::*;
 #[allow(unused_imports)]
 pub use features::*;
@@ -9,6 +10,7 @@ pub use proc_qq_codegen::*;
 pub use traits::*;
 
 mod client;
+mod data;
 mod entities;
 pub mod features;
 mod handler;
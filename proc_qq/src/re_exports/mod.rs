@@ -1,6 +1,7 @@
 pub mod anyhow;
 pub mod async_trait;
 pub mod bytes;
+pub mod chrono;
 #[cfg(feature = "console_qr")]
 pub mod image;
 pub mod qr2term;
@@ -2,10 +2,10 @@ use async_trait::async_trait;
 use ricq::structs::GroupInfo;
 use ricq_core::common::group_code2uin;
 use ricq_core::msg::MessageChain;
-use ricq_core::structs::MessageReceipt;
+use ricq_core::structs::{GroupMemberPermission, MessageReceipt};
 use ricq_core::{RQError, RQResult};
 
-use crate::{MessageTarget, MessageTargetTrait};
+use crate::{cache_member_permission, cached_member_permission, MessageTarget, MessageTargetTrait};
 
 #[async_trait]
 pub trait ClientTrait: Send + Sync {
@@ -18,6 +18,16 @@ pub trait ClientTrait: Send + Sync {
     async fn bot_uin(&self) -> i64;
 }
 
+/// 群成员的权限, 会缓存一段时间, 不需要每次获取整个成员列表
+#[async_trait]
+pub trait MemberPermissionTrait: Send + Sync {
+    async fn get_member_permission(
+        &self,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission>;
+}
+
 #[async_trait]
 impl ClientTrait for ricq::Client {
     async fn send_message_to_target<S: Into<MessageChain> + Send + Sync>(
@@ -64,6 +74,25 @@ impl ClientTrait for ricq::Client {
     }
 }
 
+#[async_trait]
+impl MemberPermissionTrait for ricq::Client {
+    async fn get_member_permission(
+        &self,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission> {
+        if let Some(permission) = cached_member_permission(self, group_code, uin) {
+            return Ok(permission);
+        }
+        let permission = self
+            .get_group_member_info(group_code, uin)
+            .await?
+            .permission;
+        cache_member_permission(self, group_code, uin, permission.clone());
+        Ok(permission)
+    }
+}
+
 #[async_trait]
 impl ClientTrait for crate::Client {
     async fn send_message_to_target<S: Into<MessageChain> + Send + Sync>(
@@ -84,3 +113,14 @@ impl ClientTrait for crate::Client {
         self.rq_client.bot_uin().await
     }
 }
+
+#[async_trait]
+impl MemberPermissionTrait for crate::Client {
+    async fn get_member_permission(
+        &self,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission> {
+        self.rq_client.get_member_permission(group_code, uin).await
+    }
+}
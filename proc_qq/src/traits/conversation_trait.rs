@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use ricq::client::event::{FriendMessageEvent, GroupMessageEvent, GroupTempMessageEvent};
+use std::time::Duration;
+
+use crate::{MessageEvent, MessageTargetTrait, NextMessage};
+
+#[async_trait]
+pub trait MessageConversationTrait: Send + Sync + MessageTargetTrait {
+    /// 等待这个会话中同一个人的下一条消息, 可以设置超时、过滤和取消,
+    /// 需要 DispatchMode::Concurrent
+    fn wait_next_message(&self) -> NextMessage;
+
+    /// 等待这个会话中同一个人的下一条消息, 超时返回None
+    async fn next_message(&self, timeout: Duration) -> Option<MessageEvent> {
+        self.wait_next_message().timeout(timeout).wait().await
+    }
+}
+
+impl MessageConversationTrait for MessageEvent {
+    fn wait_next_message(&self) -> NextMessage {
+        NextMessage::new(self.target())
+    }
+}
+
+impl MessageConversationTrait for GroupMessageEvent {
+    fn wait_next_message(&self) -> NextMessage {
+        NextMessage::new(self.target())
+    }
+}
+
+impl MessageConversationTrait for FriendMessageEvent {
+    fn wait_next_message(&self) -> NextMessage {
+        NextMessage::new(self.target())
+    }
+}
+
+impl MessageConversationTrait for GroupTempMessageEvent {
+    fn wait_next_message(&self) -> NextMessage {
+        NextMessage::new(self.target())
+    }
+}
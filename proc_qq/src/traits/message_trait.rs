@@ -4,13 +4,14 @@ use ricq_core::msg::elem::{FlashImage, FriendImage, GroupImage, Text, VideoFile}
 use ricq_core::msg::MessageChain;
 use ricq_core::pb::msg::elem::Elem;
 use ricq_core::structs::{
-    FriendMessage, GroupInfo, GroupMessage, GroupTempMessage, MessageReceipt,
+    FriendMessage, GroupInfo, GroupMemberPermission, GroupMessage, GroupTempMessage, MessageReceipt,
 };
 use ricq_core::{RQError, RQResult};
 use std::time::Duration;
 
-use crate::{ClientTrait, MessageEvent};
+use crate::{ClientTrait, MemberPermissionTrait, MessageEvent};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum MessageTarget {
     // Group(group_code,uin)
     Group(i64, i64),
@@ -149,6 +150,17 @@ impl ClientTrait for GroupMessageEvent {
     }
 }
 
+#[async_trait]
+impl MemberPermissionTrait for GroupMessageEvent {
+    async fn get_member_permission(
+        &self,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission> {
+        self.client.get_member_permission(group_code, uin).await
+    }
+}
+
 #[async_trait]
 impl MessageSendToSourceTrait for GroupMessageEvent {
     async fn send_message_to_source<S: Into<MessageChain> + Send + Sync>(
@@ -294,6 +306,17 @@ impl ClientTrait for FriendMessageEvent {
     }
 }
 
+#[async_trait]
+impl MemberPermissionTrait for FriendMessageEvent {
+    async fn get_member_permission(
+        &self,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission> {
+        self.client.get_member_permission(group_code, uin).await
+    }
+}
+
 #[async_trait]
 impl MessageSendToSourceTrait for FriendMessageEvent {
     async fn send_message_to_source<S: Into<MessageChain> + Send + Sync>(
@@ -400,6 +423,17 @@ impl ClientTrait for GroupTempMessageEvent {
     }
 }
 
+#[async_trait]
+impl MemberPermissionTrait for GroupTempMessageEvent {
+    async fn get_member_permission(
+        &self,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission> {
+        self.client.get_member_permission(group_code, uin).await
+    }
+}
+
 #[async_trait]
 impl MessageSendToSourceTrait for GroupTempMessageEvent {
     async fn send_message_to_source<S: Into<MessageChain> + Send + Sync>(
@@ -489,6 +523,17 @@ impl ClientTrait for MessageEvent {
     }
 }
 
+#[async_trait]
+impl MemberPermissionTrait for MessageEvent {
+    async fn get_member_permission(
+        &self,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission> {
+        self.client().get_member_permission(group_code, uin).await
+    }
+}
+
 #[async_trait]
 impl MessageSendToSourceTrait for MessageEvent {
     async fn send_message_to_source<S: Into<MessageChain> + Send + Sync>(
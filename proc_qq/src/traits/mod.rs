@@ -1,10 +1,12 @@
 pub use client_trait::*;
+pub use conversation_trait::*;
 pub use group_trait::*;
 pub use member_trait::*;
 pub use message_chain_trait::*;
 pub use message_trait::*;
 
 mod client_trait;
+mod conversation_trait;
 mod group_trait;
 mod member_trait;
 mod message_chain_trait;
@@ -0,0 +1,142 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Attribute, Ident, Item, ItemMod, Lit, Meta, NestedMeta};
+
+use crate::help::doc_description;
+
+// #[auto_module(id = "hello", name = "你好")] mod hello { ... }
+pub(crate) fn expand_auto_module(args: Vec<NestedMeta>, mut module: ItemMod) -> TokenStream {
+    let mut id = None;
+    let mut name = None;
+    let mut description = None;
+    let mut priority = quote! {0};
+    for arg in args {
+        let nv = match arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) => nv,
+            _ => abort!(
+                &arg.span(),
+                "格式为 #[auto_module(id = \"hello\", name = \"你好\")]"
+            ),
+        };
+        let key = nv
+            .path
+            .get_ident()
+            .map(Ident::to_string)
+            .unwrap_or_default();
+        match (key.as_str(), &nv.lit) {
+            ("id", Lit::Str(value)) => id = Some(value.value()),
+            ("name", Lit::Str(value)) => name = Some(value.value()),
+            ("description", Lit::Str(value)) => description = Some(value.value()),
+            ("priority", Lit::Int(value)) => priority = quote! {#value},
+            ("id" | "name" | "description", _) => {
+                abort!(&nv.lit.span(), "{}只支持字符串类型参数值", key)
+            }
+            ("priority", _) => abort!(&nv.lit.span(), "priority只支持数字类型参数值"),
+            _ => abort!(&nv.path.span(), "不支持的参数 : {}", key),
+        }
+    }
+    let id = match id {
+        Some(id) => id,
+        None => abort!(&module.ident.span(), "auto_module需要设置id"),
+    };
+    let name = name.unwrap_or_default();
+    // 没有设置description时使用mod的文档注释
+    let description = description.unwrap_or_else(|| doc_description(&module.attrs));
+    let module_span = module.span();
+    let items = match &mut module.content {
+        Some((_, items)) => items,
+        None => abort!(
+            module_span,
+            "auto_module只支持内联的mod, 例如 mod hello { ... }"
+        ),
+    };
+    // 按照定义的顺序收集
+    let mut handlers = vec![];
+    let mut results = vec![];
+    let mut jobs = vec![];
+    for item in items.iter() {
+        match item {
+            Item::Fn(item_fn) => {
+                let ident = &item_fn.sig.ident;
+                if has_attr(&item_fn.attrs, "event") {
+                    handlers.push(quote! {#ident {}});
+                } else if has_attr(&item_fn.attrs, "result") {
+                    results.push(quote! {#ident {}.into()});
+                } else if has_attr(&item_fn.attrs, "scheduler_job") {
+                    jobs.push(quote! {#ident.into()});
+                }
+                // #[event_fn] 生成的是#[event]结构体的方法, 不是handler, 不需要收集
+            }
+            // #[event_impl] 需要结构体的实例, 无法自动创建
+            Item::Impl(item_impl) if has_attr(&item_impl.attrs, "event_impl") => abort!(
+                &item_impl.self_ty.span(),
+                "auto_module不支持#[event_impl], 请在mod外使用 module!(\"id\", \"name\", 结构体的实例) 注册"
+            ),
+            // 指令组中的handler以及生成的help
+            Item::Mod(group) if has_attr(&group.attrs, "command_group") => {
+                let group_ident = &group.ident;
+                let help_ident = Ident::new(&format!("{}_help", group_ident), group_ident.span());
+                handlers.push(quote! {#group_ident::#help_ident {}});
+                if let Some((_, group_items)) = &group.content {
+                    for group_item in group_items {
+                        if let Item::Fn(item_fn) = group_item {
+                            if has_attr(&item_fn.attrs, "event") {
+                                let ident = &item_fn.sig.ident;
+                                handlers.push(quote! {#group_ident::#ident {}});
+                            }
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    items.push(syn::parse_quote! {
+        pub fn module() -> ::proc_qq::Module {
+            ::proc_qq::Module {
+                id: #id.to_owned(),
+                name: #name.to_owned(),
+                description: #description.to_owned(),
+                priority: #priority,
+                // 和 module! 一样通过IntoModuleEventHandlers转换
+                handles: {
+                    #[allow(unused_mut)]
+                    let mut handles: Vec<::proc_qq::ModuleEventHandler> = vec![];
+                    #(handles.extend(::proc_qq::IntoModuleEventHandlers::into_handlers(#handlers));)*
+                    handles
+                },
+            }
+        }
+    });
+    if !results.is_empty() {
+        items.push(syn::parse_quote! {
+            pub fn result_handlers() -> Vec<::proc_qq::EventResultHandler> {
+                vec![#(#results),*]
+            }
+        });
+    }
+    // #[scheduler_job]需要scheduler特性, 有定时任务时才生成
+    if !jobs.is_empty() {
+        items.push(syn::parse_quote! {
+            pub fn scheduler() -> ::proc_qq::Scheduler {
+                ::proc_qq::Scheduler {
+                    id: #id.to_owned(),
+                    jobs: vec![#(#jobs),*],
+                }
+            }
+        });
+    }
+    quote! {#module}
+}
+
+// #[event] 或 #[proc_qq::event]
+fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name)
+    })
+}
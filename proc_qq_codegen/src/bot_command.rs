@@ -2,14 +2,21 @@ use std::ops::Deref;
 
 use proc_macro2::Ident;
 use proc_macro_error::abort;
-use syn::{FnArg, ItemFn, Pat, Type};
+use syn::spanned::Spanned;
+use syn::{FnArg, GenericArgument, ItemFn, LitStr, Pat, PathArguments, Type};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum BotCommandRaw {
-    Command(String),
+    // 固定字符串, 多个时为别名 /ban|/禁言
+    Command(Vec<String>),
     Param(String),
     Enum(String, Vec<String>),
     Multiple(Vec<BotCommandRawTuple>),
+    Rest(String),
+    // [{time}] 可以省略的参数
+    Optional(Box<BotCommandRaw>),
+    // --silent|-s 或 -t {time}, (名称, 值的参数名)
+    Flag(Vec<String>, Option<String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -21,10 +28,17 @@ pub(crate) enum BotCommandRawTuple {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BotParamsMather<'a> {
-    Command(String),
+    Command(Vec<String>),
     Params(&'a Ident, &'a Type),
     Enum(&'a Ident, &'a Type, Vec<String>),
     Multiple(Vec<BotParamsMatherTuple<'a>>),
+    Rest(&'a Ident, &'a Type),
+    // (参数名, Option<T>, T, 枚举值)
+    OptionalEnum(&'a Ident, &'a Type, &'a Type, Vec<String>),
+    // bool参数, 出现flag时为true
+    Flag(&'a Ident, &'a Type, Vec<String>),
+    // (参数名, Option<T>, T, 名称)
+    ValueFlag(&'a Ident, &'a Type, &'a Type, Vec<String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -34,43 +48,87 @@ pub enum BotParamsMatherTuple<'a> {
     Enum(&'a Ident, &'a Type, Vec<String>),
 }
 
-const COMMAND_NOTICE: &str = r#"bot_command中的元素必须是由固定字符串和参数组合而成, 例如 "/删除 {idx}" "请{min}{time:|小时|分钟|秒钟}后提醒我{event}" "{option:|开启|关闭} 天气预报" "#;
+const COMMAND_NOTICE: &str = r#"bot_command中的元素必须是由固定字符串和参数组合而成, 例如 "/删除 {idx}" "请{min}{time:|小时|分钟|秒钟}后提醒我{event}" "{option:|开启|关闭} 天气预报" "/说 {text..}" "/ban|/禁言 {user} [{time}] --silent -r {reason}" "#;
 
 // 解析命令行
-pub(crate) fn parse_bot_command(
-    method: &ItemFn,
-    bot_command: Option<String>,
-) -> Option<Vec<BotCommandRaw>> {
+pub(crate) fn parse_bot_command(bot_command: Option<LitStr>) -> Option<Vec<BotCommandRaw>> {
     // 由固定字符串和参数组合而成
     if let Some(bot_command) = bot_command {
+        let span = bot_command.span();
+        let bot_command = bot_command.value();
         //
-        let command_reg = regex::Regex::new(r#"^[A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+$"#)
-            .expect("proc_qq正则错误(command_reg)");
+        let command_reg = regex::Regex::new(
+            r#"^[A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+(\|[A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+)*$"#,
+        )
+        .expect("proc_qq正则错误(command_reg)");
         let params_reg = regex::Regex::new(r#"^\{[A-Za-z_]([A-Za-z0-9_]+)?(:(\|[A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+)+)?\}$"#)
             .expect("proc_qq正则错误(params_reg)");
+        let rest_reg = regex::Regex::new(r#"^\{[A-Za-z_]([A-Za-z0-9_]+)?\.\.\}$"#)
+            .expect("proc_qq正则错误(rest_reg)");
+        let flag_reg = regex::Regex::new(
+            r#"^(--[A-Za-z][A-Za-z0-9_-]*|-[A-Za-z])(\|(--[A-Za-z][A-Za-z0-9_-]*|-[A-Za-z]))*$"#,
+        )
+        .expect("proc_qq正则错误(flag_reg)");
+        let flag_value_reg = regex::Regex::new(r#"^\{[A-Za-z_]([A-Za-z0-9_]+)?\}$"#)
+            .expect("proc_qq正则错误(flag_value_reg)");
         let tuple_reg = regex::Regex::new(r#"^(([A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+)|(\{[A-Za-z_]([A-Za-z0-9_]+)?(:(\|[A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+)+)?\}))+$"#)
             .expect("proc_qq正则错误(tuple_reg)");
         //
-        let mut bot_command_items = vec![];
-        let mut bot_command_item_strs = bot_command.split_whitespace();
+        let mut bot_command_items: Vec<BotCommandRaw> = vec![];
+        let mut bot_command_item_strs = bot_command.split_whitespace().peekable();
         // 根据空格切分并循环
         while let Some(item) = bot_command_item_strs.next() {
-            if command_reg.is_match(item) {
-                bot_command_items.push(BotCommandRaw::Command(item.to_owned()));
-            } else if params_reg.is_match(item) {
-                let param = &item[1..item.len() - 1];
-                if param.contains(":|") {
-                    let idx = param.find(":|").unwrap();
-                    let param_name = &param[..idx];
-                    let param_enum_str = &param[idx + 2..];
-                    let param_enums = param_enum_str.split('|');
-                    bot_command_items.push(BotCommandRaw::Enum(
-                        param_name.to_string(),
-                        param_enums.map(|s| s.to_string()).collect(),
-                    ));
-                } else {
-                    bot_command_items.push(BotCommandRaw::Param(param.to_string()));
+            // flag必须写在最后, {text..}之后只能是flag, [..]之后只能是[..]或flag
+            let is_flag = flag_reg.is_match(item);
+            if !is_flag {
+                for before in &bot_command_items {
+                    let notice = match before {
+                        BotCommandRaw::Flag(..) => "flag必须写在bot_command的最后",
+                        BotCommandRaw::Rest(_) => "{text..} 会取得剩下的全部文本, 之后只能是flag",
+                        BotCommandRaw::Optional(_) if !item.starts_with('[') => {
+                            "可以省略的参数 [..] 之后只能是 [..] 或flag"
+                        }
+                        _ => continue,
+                    };
+                    abort!(span, "{} => {}", notice, item);
                 }
+            }
+            if is_flag {
+                let names = item.split('|').map(String::from).collect();
+                let value = match bot_command_item_strs.peek() {
+                    Some(next) if flag_value_reg.is_match(next) => {
+                        let next = bot_command_item_strs.next().unwrap();
+                        Some(next[1..next.len() - 1].to_string())
+                    }
+                    _ => None,
+                };
+                bot_command_items.push(BotCommandRaw::Flag(names, value));
+            } else if item.starts_with('[') {
+                if !item.ends_with(']') || item.len() < 2 {
+                    abort!(span, "可以省略的参数必须写成 [{{param}}] => {}", item);
+                }
+                let inner = &item[1..item.len() - 1];
+                let inner = if params_reg.is_match(inner) {
+                    parse_param(inner)
+                } else if rest_reg.is_match(inner) {
+                    BotCommandRaw::Rest(inner[1..inner.len() - 3].to_string())
+                } else {
+                    abort!(
+                        span,
+                        "[..] 中只能是一个参数, 例如 [{{time}}] [{{unit:|秒|分}}] [{{text..}}] => {}",
+                        item
+                    );
+                };
+                bot_command_items.push(BotCommandRaw::Optional(Box::new(inner)));
+            } else if command_reg.is_match(item) {
+                bot_command_items.push(BotCommandRaw::Command(
+                    item.split('|').map(String::from).collect(),
+                ));
+            } else if params_reg.is_match(item) {
+                bot_command_items.push(parse_param(item));
+            } else if rest_reg.is_match(item) {
+                let param = &item[1..item.len() - 3];
+                bot_command_items.push(BotCommandRaw::Rest(param.to_string()));
             } else if tuple_reg.is_match(item) {
                 let mut bot_command_elements = vec![];
                 let find_reg = regex::Regex::new(r#"(([A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+)|(\{[A-Za-z_]([A-Za-z0-9_]+)?(:(\|[A-Za-z0-9_/\p{Han}\p{Hiragana}\p{Katakana}]+)+)?\}))"#).expect("proc_qq正则错误(find_reg)");
@@ -97,10 +155,7 @@ pub(crate) fn parse_bot_command(
                 }
                 bot_command_items.push(BotCommandRaw::Multiple(bot_command_elements))
             } else {
-                abort!(
-                    &method.sig.ident.span(),
-                    format!("{} => {}", COMMAND_NOTICE, item)
-                );
+                abort!(span, format!("{} => {}", COMMAND_NOTICE, item));
             }
         }
         Some(bot_command_items)
@@ -109,6 +164,22 @@ pub(crate) fn parse_bot_command(
     }
 }
 
+// {param} 或 {param:|a|b}
+fn parse_param(item: &str) -> BotCommandRaw {
+    let param = &item[1..item.len() - 1];
+    if let Some(idx) = param.find(":|") {
+        let param_name = &param[..idx];
+        let param_enum_str = &param[idx + 2..];
+        let param_enums = param_enum_str.split('|');
+        BotCommandRaw::Enum(
+            param_name.to_string(),
+            param_enums.map(|s| s.to_string()).collect(),
+        )
+    } else {
+        BotCommandRaw::Param(param.to_string())
+    }
+}
+
 // 将命令行跟参数进行匹配
 pub(crate) fn parse_bot_args<'a>(
     method: &'a ItemFn,
@@ -148,6 +219,44 @@ pub(crate) fn parse_bot_args<'a>(
                     }
                     BotParamsMather::Multiple(multiple_result)
                 }
+                BotCommandRaw::Rest(tmp) => {
+                    let (pat, ty) = take_param(method, args_iter.next(), tmp.as_str());
+                    BotParamsMather::Rest(pat, ty)
+                }
+                BotCommandRaw::Optional(inner) => match *inner {
+                    BotCommandRaw::Param(tmp) => {
+                        let (pat, ty) = take_param(method, args_iter.next(), tmp.as_str());
+                        option_inner(ty, "[{param}]");
+                        BotParamsMather::Params(pat, ty)
+                    }
+                    BotCommandRaw::Enum(tmp, e) => {
+                        let (pat, ty) = take_param(method, args_iter.next(), tmp.as_str());
+                        let inner = option_inner(ty, "[{param:|..}]");
+                        BotParamsMather::OptionalEnum(pat, ty, inner, e)
+                    }
+                    BotCommandRaw::Rest(tmp) => {
+                        let (pat, ty) = take_param(method, args_iter.next(), tmp.as_str());
+                        option_inner(ty, "[{text..}]");
+                        BotParamsMather::Rest(pat, ty)
+                    }
+                    _ => unreachable!(),
+                },
+                BotCommandRaw::Flag(names, None) => {
+                    let (pat, ty) = take_param(method, args_iter.next(), &flag_param_name(&names));
+                    if !matches!(ty, Type::Path(path) if path.path.is_ident("bool")) {
+                        abort!(
+                            &ty.span(),
+                            "不带值的flag {} 只能对应bool类型的参数",
+                            names.join("|")
+                        );
+                    }
+                    BotParamsMather::Flag(pat, ty, names)
+                }
+                BotCommandRaw::Flag(names, Some(tmp)) => {
+                    let (pat, ty) = take_param(method, args_iter.next(), tmp.as_str());
+                    let inner = option_inner(ty, "带值的flag");
+                    BotParamsMather::ValueFlag(pat, ty, inner, names)
+                }
             });
         }
         Some(result)
@@ -156,6 +265,31 @@ pub(crate) fn parse_bot_args<'a>(
     }
 }
 
+// --dry-run => dry_run, -s => s
+fn flag_param_name(names: &[String]) -> String {
+    let name = names
+        .iter()
+        .find(|name| name.starts_with("--"))
+        .unwrap_or(&names[0]);
+    name.trim_start_matches('-').replace('-', "_")
+}
+
+// 可以省略的参数必须是Option<T>, 返回T
+fn option_inner<'a>(ty: &'a Type, what: &str) -> &'a Type {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Option" {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                        return inner;
+                    }
+                }
+            }
+        }
+    }
+    abort!(&ty.span(), "{} 对应的参数必须是Option<T>类型", what);
+}
+
 fn take_param<'a>(method: &'a ItemFn, arg: Option<&&'a FnArg>, tmp: &str) -> (&'a Ident, &'a Type) {
     if let Some(arg) = arg {
         match arg {
@@ -172,7 +306,7 @@ fn take_param<'a>(method: &'a ItemFn, arg: Option<&&'a FnArg>, tmp: &str) -> (&'
                             return (ident, ty);
                         } else {
                             abort!(
-                                &method.sig.ident.span(),
+                                &pi.ident.span(),
                                 "bot_command中的参数名必须与参数名一致 {} != {}",
                                 tmp,
                                 pi.ident,
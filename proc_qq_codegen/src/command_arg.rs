@@ -0,0 +1,144 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, Lit, Meta, NestedMeta, Variant};
+
+// #[derive(CommandArg)]
+pub(crate) fn derive_command_arg(input: DeriveInput) -> TokenStream {
+    if !input.generics.params.is_empty() {
+        abort!(&input.generics.span(), "CommandArg不支持泛型");
+    }
+    match &input.data {
+        Data::Enum(data) => {
+            let variants: Vec<(&Variant, Vec<String>)> = data
+                .variants
+                .iter()
+                .map(|variant| (variant, variant_names(variant)))
+                .collect();
+            derive_enum(&input, variants)
+        }
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let inner = &fields.unnamed.first().unwrap().ty;
+                derive_newtype(&input, quote! {#inner})
+            }
+            _ => abort!(
+                &input.ident.span(),
+                "CommandArg只支持枚举和只有一个字段的元组结构体 (struct Minutes(u64))"
+            ),
+        },
+        Data::Union(_) => abort!(&input.ident.span(), "CommandArg不支持union"),
+    }
+}
+
+// 变体的名称以及 #[arg(alias = "开")] 设置的别名
+fn variant_names(variant: &Variant) -> Vec<String> {
+    if !matches!(variant.fields, Fields::Unit) {
+        abort!(&variant.span(), "CommandArg的枚举只支持没有字段的变体");
+    }
+    let mut names = vec![variant.ident.to_string()];
+    for attr in &variant.attrs {
+        if !attr.path.is_ident("arg") {
+            continue;
+        }
+        let list = match attr.parse_meta() {
+            Ok(Meta::List(list)) => list,
+            _ => abort!(&attr.span(), "格式为 #[arg(alias = \"开\")]"),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("alias") => {
+                    match nv.lit {
+                        Lit::Str(value) if !value.value().trim().is_empty() => {
+                            names.push(value.value().trim().to_string())
+                        }
+                        _ => abort!(&nv.lit.span(), "alias只支持不为空的字符串类型参数值"),
+                    }
+                }
+                _ => abort!(
+                    &nested.span(),
+                    "arg只支持alias, 例如 #[arg(alias = \"开\")]"
+                ),
+            }
+        }
+    }
+    names
+}
+
+fn derive_enum(input: &DeriveInput, variants: Vec<(&Variant, Vec<String>)>) -> TokenStream {
+    let ident = &input.ident;
+    let ident_str = ident.to_string();
+    // 变体名和别名都不区分ASCII大小写, 整个参数和前缀匹配时相同
+    let mut try_from = quote! {};
+    // 指令中没有空白分隔时 ("{mode}天气预报") 按照前缀匹配, 较长的名称优先
+    let mut prefixes = vec![];
+    for (variant, names) in &variants {
+        let variant_ident = &variant.ident;
+        try_from.extend(quote! {
+            if #(value.eq_ignore_ascii_case(#names))||* {
+                return Ok(Self::#variant_ident);
+            }
+        });
+        for name in names {
+            prefixes.push((name.clone(), variant_ident));
+        }
+    }
+    prefixes.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+    let prefixes = prefixes.into_iter().map(|(name, variant_ident)| {
+        quote! {
+            if matcher.match_command_ignore_case(#name) {
+                return Some(Self::#variant_ident);
+            }
+        }
+    });
+    quote! {
+        impl ::proc_qq::TryFromStr for #ident {
+            fn try_from(value: &str) -> ::std::result::Result<Self, ::proc_qq::re_exports::anyhow::Error> {
+                #try_from
+                Err(::proc_qq::re_exports::anyhow::Error::msg(format!("{} 不能转换为 {}", value, #ident_str)))
+            }
+        }
+
+        impl ::proc_qq::FromCommandMatcher for #ident {
+            fn get(matcher: &mut ::proc_qq::CommandMatcher) -> Option<Self> {
+                matcher.take_token_if(|token| <Self as ::proc_qq::TryFromStr>::try_from(token).ok())
+            }
+        }
+
+        impl ::proc_qq::FromTupleMatcher for #ident {
+            fn get(matcher: &mut ::proc_qq::TupleMatcher) -> Option<Self> {
+                #(#prefixes)*
+                None
+            }
+        }
+
+        impl ::proc_qq::CommandArg for #ident {}
+    }
+}
+
+// 元组结构体使用内部类型的实现
+fn derive_newtype(input: &DeriveInput, inner: TokenStream) -> TokenStream {
+    let ident = &input.ident;
+    quote! {
+        impl ::proc_qq::TryFromStr for #ident {
+            fn try_from(value: &str) -> ::std::result::Result<Self, ::proc_qq::re_exports::anyhow::Error> {
+                <#inner as ::proc_qq::TryFromStr>::try_from(value).map(Self)
+            }
+        }
+
+        impl ::proc_qq::FromCommandMatcher for #ident {
+            fn get(matcher: &mut ::proc_qq::CommandMatcher) -> Option<Self> {
+                ::proc_qq::matcher_get::<#inner>(matcher).map(Self)
+            }
+        }
+
+        impl ::proc_qq::FromTupleMatcher for #ident {
+            fn get(matcher: &mut ::proc_qq::TupleMatcher) -> Option<Self> {
+                ::proc_qq::tuple_matcher_get::<#inner>(matcher).map(Self)
+            }
+        }
+
+        impl ::proc_qq::CommandArg for #ident {}
+    }
+}
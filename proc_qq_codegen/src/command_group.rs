@@ -0,0 +1,227 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Ident, Item, ItemMod, Lit, LitStr, Meta, MetaNameValue, NestedMeta, Token};
+
+use crate::event_arg::bracket_list_to_paren;
+
+// #[command_group(name = "/admin", require = "group_admin")] mod admin { ... }
+pub(crate) fn expand_command_group(args: Vec<NestedMeta>, mut module: ItemMod) -> TokenStream {
+    let mut name: Option<LitStr> = None;
+    let mut description: Option<String> = None;
+    // 除name和description之外的参数, 添加到组内的每个#[event]上
+    let mut shared: Vec<NestedMeta> = vec![];
+    for arg in args {
+        match &arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => match &nv.lit {
+                Lit::Str(value) if !value.value().trim().is_empty() => name = Some(value.clone()),
+                _ => abort!(&nv.lit.span(), "name只支持不为空的字符串类型参数值"),
+            },
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("description") => {
+                match &nv.lit {
+                    Lit::Str(value) => description = Some(value.value()),
+                    _ => abort!(&nv.lit.span(), "description只支持字符串类型参数值"),
+                }
+            }
+            NestedMeta::Meta(meta) if meta.path().is_ident("bot_command") => {
+                abort!(&meta.span(), "command_group使用name设置指令组的名称")
+            }
+            _ => shared.push(arg),
+        }
+    }
+    let name = match name {
+        Some(name) => name,
+        None => abort!(
+            &module.ident.span(),
+            "command_group需要设置name, 例如 #[command_group(name = \"/admin\")]"
+        ),
+    };
+    let module_span = module.span();
+    let items = match &mut module.content {
+        Some((_, items)) => items,
+        None => abort!(
+            module_span,
+            "command_group只支持内联的mod, 例如 mod admin { ... }"
+        ),
+    };
+    let mut commands = vec![];
+    for item in items.iter_mut() {
+        if let Item::Fn(item_fn) = item {
+            for attr in item_fn.attrs.iter_mut() {
+                if is_event_attr(attr) {
+                    commands.push(group_event_attr(attr, &name, &shared));
+                }
+            }
+        }
+    }
+    if commands.is_empty() {
+        abort!(&module.ident.span(), "command_group中没有#[event]");
+    }
+    // 只发送指令组的名称时, 回复组内的指令
+    let doc = description.unwrap_or_else(|| "查看指令组中的指令".to_owned());
+    let reply = commands.join("\n");
+    // handler的名称为 组名_help, 例如 admin::admin_help
+    let help_ident = Ident::new(&format!("{}_help", module.ident), module.ident.span());
+    if items
+        .iter()
+        .any(|item| item_ident(item) == Some(&help_ident))
+    {
+        abort!(
+            &help_ident.span(),
+            "command_group会生成名为{}的handler, 组内不能再定义这个名称",
+            help_ident
+        );
+    }
+    // #[event]按照类型名称识别事件, 放在单独的mod中引入MessageEvent, 避免与组内的use冲突
+    let help: Item = syn::parse_quote! {
+        mod __proc_qq_command_group_help {
+            use ::proc_qq::{MessageChainParseTrait, MessageEvent, MessageSendToSourceTrait};
+
+            #[doc = #doc]
+            #[::proc_qq::event(bot_command = #name, #(#shared),*)]
+            async fn #help_ident(event: &MessageEvent) -> ::proc_qq::re_exports::anyhow::Result<bool> {
+                event.send_message_to_source(#reply.parse_message_chain()).await?;
+                Ok(true)
+            }
+        }
+    };
+    items.push(help);
+    items.push(syn::parse_quote! {
+        pub use self::__proc_qq_command_group_help::#help_ident;
+    });
+    quote! {#module}
+}
+
+fn item_ident(item: &Item) -> Option<&Ident> {
+    match item {
+        Item::Fn(item) => Some(&item.sig.ident),
+        Item::Struct(item) => Some(&item.ident),
+        Item::Enum(item) => Some(&item.ident),
+        Item::Const(item) => Some(&item.ident),
+        Item::Static(item) => Some(&item.ident),
+        Item::Mod(item) => Some(&item.ident),
+        Item::Type(item) => Some(&item.ident),
+        Item::Trait(item) => Some(&item.ident),
+        Item::Union(item) => Some(&item.ident),
+        _ => None,
+    }
+}
+
+fn is_event_attr(attr: &Attribute) -> bool {
+    attr.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "event")
+}
+
+// 给bot_command加上组的名称, 并添加组的参数, 返回去掉多余空白的完整指令
+fn group_event_attr(attr: &mut Attribute, name: &LitStr, shared: &[NestedMeta]) -> String {
+    let tokens = match attr.parse_args::<TokenStream>() {
+        Ok(tokens) => tokens,
+        Err(_) => abort!(&attr.span(), "command_group中的#[event]需要设置bot_command"),
+    };
+    let args = match Punctuated::<NestedMeta, Token![,]>::parse_terminated
+        .parse2(bracket_list_to_paren(tokens))
+    {
+        Ok(args) => args,
+        Err(err) => abort!(err.span(), "{}", err),
+    };
+    let mut command = None;
+    let mut args: Vec<NestedMeta> = args
+        .into_iter()
+        .map(|arg| match arg {
+            NestedMeta::Meta(Meta::NameValue(mut nv)) if nv.path.is_ident("bot_command") => {
+                let value = match &nv.lit {
+                    Lit::Str(value) => value.value(),
+                    _ => abort!(&nv.lit.span(), "bot_command只支持字符串类型参数值"),
+                };
+                let full = format!("{} {}", name.value(), value);
+                command = Some(full.split_whitespace().collect::<Vec<_>>().join(" "));
+                nv.lit = Lit::Str(LitStr::new(&full, nv.lit.span()));
+                NestedMeta::Meta(Meta::NameValue(nv))
+            }
+            arg => arg,
+        })
+        .collect();
+    let command = match command {
+        Some(command) => command,
+        None => abort!(&attr.span(), "command_group中的#[event]需要设置bot_command"),
+    };
+    for arg in shared {
+        let meta = match arg {
+            NestedMeta::Meta(meta) => meta,
+            NestedMeta::Lit(_) => {
+                args.push(arg.clone());
+                continue;
+            }
+        };
+        let key = meta.path().get_ident().map(ToString::to_string);
+        // 组设置了冷却时, event中也不能修改per和burst
+        let same_keys: &[&str] = match key.as_deref() {
+            Some("cooldown" | "per" | "burst") => &["cooldown", "per", "burst"],
+            _ => &[],
+        };
+        let position = args.iter().position(|other| match other {
+            NestedMeta::Meta(other) => {
+                other.path() == meta.path()
+                    || same_keys.iter().any(|same| other.path().is_ident(same))
+            }
+            NestedMeta::Lit(_) => false,
+        });
+        match (key.as_deref(), position) {
+            // 权限需要全部满足, 合并组和event的require
+            (Some("require"), Some(position)) => {
+                args[position] = merge_require(meta, &args[position]);
+            }
+            // 冷却是组的限制, 不能在event中修改
+            (Some("cooldown" | "per" | "burst"), Some(position)) => abort!(
+                &args[position].span(),
+                "command_group中已经设置了{}, 不能在#[event]中修改冷却",
+                key.unwrap_or_default()
+            ),
+            // 其他选项可以在event中覆盖
+            (
+                Some(
+                    "priority" | "timeout" | "cooldown_reply" | "require_reply" | "usage"
+                    | "usage_reply",
+                ),
+                Some(_),
+            ) => (),
+            // 事件参数(过滤条件)需要同时满足, 与event中的参数一起添加
+            _ => args.push(arg.clone()),
+        }
+    }
+    let path = &attr.path;
+    *attr = syn::parse_quote! { #[#path(#(#args),*)] };
+    command
+}
+
+// require = "group_admin" 与 require = "bot_admin" 合并为 require = "group_admin,bot_admin"
+fn merge_require(group: &Meta, event: &NestedMeta) -> NestedMeta {
+    let requires = |meta: &Meta| match meta {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(value),
+            ..
+        }) => value
+            .value()
+            .split(',')
+            .map(|name| name.trim().to_owned())
+            .collect::<Vec<_>>(),
+        _ => abort!(&meta.span(), "require只支持字符串类型参数值"),
+    };
+    let mut event = match event {
+        NestedMeta::Meta(Meta::NameValue(nv)) => nv.clone(),
+        _ => abort!(&event.span(), "require只支持字符串类型参数值"),
+    };
+    let mut merged = requires(group);
+    for name in requires(&Meta::NameValue(event.clone())) {
+        if !merged.contains(&name) {
+            merged.push(name);
+        }
+    }
+    event.lit = Lit::Str(LitStr::new(&merged.join(","), event.lit.span()));
+    NestedMeta::Meta(Meta::NameValue(event))
+}
@@ -0,0 +1,48 @@
+use proc_macro2::TokenStream;
+use quote::{quote, TokenStreamExt};
+use syn::{FnArg, GenericArgument, PathArguments, PathSegment, Type};
+
+// 参数类型为Data<T>时, 从ClientBuilder::data设置的共享数据中获取.
+// 过程宏无法知道use引入的是哪个Data, 只识别 Data<T> proc_qq::Data<T> ::proc_qq::Data<T>,
+// 自定义的 Data 结构体 (没有泛型参数或者在其他路径中) 仍然作为指令的参数
+pub(crate) fn is_data_param(arg: &FnArg) -> bool {
+    let tp = match arg {
+        FnArg::Typed(pt) => match pt.ty.as_ref() {
+            Type::Path(tp) if tp.qself.is_none() => tp,
+            _ => return false,
+        },
+        FnArg::Receiver(_) => return false,
+    };
+    let segments: Vec<&PathSegment> = tp.path.segments.iter().collect();
+    let last = match segments.as_slice() {
+        [last] if tp.path.leading_colon.is_none() => last,
+        [krate, last] if krate.ident == "proc_qq" && krate.arguments.is_empty() => last,
+        _ => return false,
+    };
+    last.ident == "Data"
+        && match &last.arguments {
+            PathArguments::AngleBracketed(args) => {
+                args.args.len() == 1 && matches!(args.args.first(), Some(GenericArgument::Type(_)))
+            }
+            _ => false,
+        }
+}
+
+// 取出Data参数, 返回获取共享数据的语句以及剩余的参数
+pub(crate) fn take_data_params<'a>(args: &[&'a FnArg]) -> (TokenStream, Vec<&'a FnArg>) {
+    let mut data_lets = quote! {};
+    let mut others = vec![];
+    for arg in args {
+        match arg {
+            FnArg::Typed(pt) if is_data_param(arg) => {
+                let pat = pt.pat.as_ref();
+                let ty = pt.ty.as_ref();
+                data_lets.append_all(quote! {
+                    let #pat: #ty = <#ty>::from_context()?;
+                });
+            }
+            _ => others.push(*arg),
+        }
+    }
+    (data_lets, others)
+}
@@ -1,10 +1,11 @@
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
 use proc_macro_error::abort;
 use quote::{quote, TokenStreamExt};
 use syn::spanned::Spanned;
 use syn::Lit::Str;
 use syn::Meta::{List, NameValue, Path};
 use syn::NestedMeta::{Lit, Meta};
-use syn::{AttributeArgs, ItemFn, NestedMeta};
+use syn::{AttributeArgs, FnArg, ItemFn, LitStr, NestedMeta, Pat};
 
 #[derive(Clone, Debug)]
 pub(crate) enum EventArg {
@@ -15,7 +16,127 @@ pub(crate) enum EventArg {
     Eq(String),
     TrimRegexp(String),
     TrimEq(String),
-    BotCommand(String),
+    BotCommand(LitStr),
+    Group(Vec<i64>),
+    User(Vec<i64>),
+    // ChatKind的成员名称
+    Chat(Vec<&'static str>),
+    AtBot,
+    TargetIsBot,
+}
+
+// 除了消息以外可以使用event参数的事件, 对应proc_qq中HandEvent的表
+const NOT_MESSAGE_EVENTS: &[&str] = &[
+    "& GroupPokeEvent",
+    "& FriendPokeEvent",
+    "& NewMemberEvent",
+    "& GroupMuteEvent",
+    "& GroupLeaveEvent",
+    "& GroupDisbandEvent",
+    "& GroupNameUpdateEvent",
+    "& GroupMessageRecallEvent",
+    "& FriendMessageRecallEvent",
+    "& MemberPermissionChangeEvent",
+    "& JoinGroupRequestEvent",
+    "& SelfInvitedEvent",
+    "& NewFriendRequestEvent",
+    "& NewFriendEvent",
+    "& DeleteFriendEvent",
+    "& GroupAudioMessageEvent",
+    "& FriendAudioMessageEvent",
+];
+
+pub(crate) fn is_not_message_event(param_ty: &str) -> bool {
+    NOT_MESSAGE_EVENTS.contains(&param_ty)
+}
+
+// 消息内容相关的参数只能用于消息事件, target_is_bot只能用于其他事件
+pub(crate) fn check_args_for_event(method: &ItemFn, all: &[EventArg], is_message_event: bool) {
+    for x in all {
+        let name = match x {
+            EventArg::All(args) | EventArg::Any(args) | EventArg::Not(args) => {
+                check_args_for_event(method, args, is_message_event);
+                continue;
+            }
+            EventArg::Regexp(_) => "regexp",
+            EventArg::Eq(_) => "eq",
+            EventArg::TrimRegexp(_) => "trim_regexp",
+            EventArg::TrimEq(_) => "trim_eq",
+            EventArg::BotCommand(_) => "bot_command",
+            EventArg::Chat(_) => "chat",
+            EventArg::AtBot => "at_bot",
+            EventArg::TargetIsBot => {
+                if is_message_event {
+                    abort!(&method.sig.span(), "target_is_bot不支持消息类型事件");
+                }
+                continue;
+            }
+            EventArg::Group(_) | EventArg::User(_) => continue,
+        };
+        if !is_message_event {
+            abort!(
+                &method.sig.span(),
+                "{}只支持消息类型事件 (MessageEvent,*MessageEvent)",
+                name
+            );
+        }
+    }
+}
+
+// syn的NameValue只支持字面量, 将 group = [1, 2] 转换为 group(1, 2) 再解析
+pub(crate) fn bracket_list_to_paren(input: TokenStream) -> TokenStream {
+    let mut output = vec![];
+    let mut tokens = input.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '=' => {
+                let is_ident = matches!(output.last(), Some(TokenTree::Ident(_)));
+                match tokens.peek() {
+                    Some(TokenTree::Group(group))
+                        if is_ident && group.delimiter() == Delimiter::Bracket =>
+                    {
+                        let mut paren = Group::new(Delimiter::Parenthesis, group.stream());
+                        paren.set_span(group.span());
+                        output.push(TokenTree::Group(paren));
+                        tokens.next();
+                    }
+                    _ => output.push(TokenTree::Punct(punct)),
+                }
+            }
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Parenthesis => {
+                let mut paren = Group::new(
+                    Delimiter::Parenthesis,
+                    bracket_list_to_paren(group.stream()),
+                );
+                paren.set_span(group.span());
+                output.push(TokenTree::Group(paren));
+            }
+            token => output.push(token),
+        }
+    }
+    output.into_iter().collect()
+}
+
+fn parse_uin_list(list: &syn::MetaList, name: &str) -> Vec<i64> {
+    let mut uin_list = vec![];
+    for x in &list.nested {
+        match x {
+            Lit(syn::Lit::Int(value)) => match value.base10_parse::<i64>() {
+                Ok(uin) => uin_list.push(uin),
+                Err(_) => abort!(&value.span(), "{}只支持i64类型的整数", name),
+            },
+            _ => abort!(
+                &x.span(),
+                "{}只支持整数类型参数值, 例如 {} = [123, 456]",
+                name,
+                name
+            ),
+        }
+    }
+    if uin_list.is_empty() {
+        abort!(&list.span(), "{}至少需要一个号码", name);
+    }
+    uin_list
 }
 
 // 递归匹配表达式
@@ -24,7 +145,13 @@ pub(crate) fn parse_args(children: Vec<NestedMeta>) -> Vec<EventArg> {
     for nm in children {
         match nm {
             Meta(meta) => match meta {
-                Path(_) => abort!(&meta.span(), "不支持的参数名称"),
+                Path(path) => match path.get_ident().map(|ident| ident.to_string()) {
+                    Some(name) if name == "at_bot" => children_args.push(EventArg::AtBot),
+                    Some(name) if name == "target_is_bot" => {
+                        children_args.push(EventArg::TargetIsBot)
+                    }
+                    _ => abort!(&path.span(), "不支持的参数名称"),
+                },
                 List(list) => {
                     if list.path.segments.len() != 1 {
                         abort!(&list.span(), "表达式有且只能有一个片段");
@@ -53,6 +180,12 @@ pub(crate) fn parse_args(children: Vec<NestedMeta>) -> Vec<EventArg> {
                             }
                             children_args.push(EventArg::Any(parse_args(v)));
                         }
+                        "group" => {
+                            children_args.push(EventArg::Group(parse_uin_list(&list, "group")));
+                        }
+                        "user" => {
+                            children_args.push(EventArg::User(parse_uin_list(&list, "user")));
+                        }
                         _ => abort!(&ident.span(), "不支持的参数名称"),
                     }
                 }
@@ -70,8 +203,8 @@ pub(crate) fn parse_args(children: Vec<NestedMeta>) -> Vec<EventArg> {
                                     Ok(_) => {
                                         children_args.push(EventArg::Regexp(v));
                                     }
-                                    Err(_) => {
-                                        abort!(&ident.span(), "正则表达式不正确");
+                                    Err(err) => {
+                                        abort!(&value.span(), "正则表达式不正确 : {}", err);
                                     }
                                 }
                             }
@@ -90,8 +223,8 @@ pub(crate) fn parse_args(children: Vec<NestedMeta>) -> Vec<EventArg> {
                                     Ok(_) => {
                                         children_args.push(EventArg::TrimRegexp(v));
                                     }
-                                    Err(_) => {
-                                        abort!(&ident.span(), "正则表达式不正确");
+                                    Err(err) => {
+                                        abort!(&value.span(), "正则表达式不正确 : {}", err);
                                     }
                                 }
                             }
@@ -105,10 +238,28 @@ pub(crate) fn parse_args(children: Vec<NestedMeta>) -> Vec<EventArg> {
                         },
                         "bot_command" => match nv.lit {
                             Str(value) => {
-                                children_args.push(EventArg::BotCommand(value.value()));
+                                children_args.push(EventArg::BotCommand(value));
                             }
                             _ => abort!(&ident.span(), "bot_command只支持字符串类型参数值"),
                         },
+                        "chat" => match nv.lit {
+                            Str(value) => {
+                                let mut kinds = vec![];
+                                for kind in value.value().split('|').map(str::trim) {
+                                    kinds.push(match kind {
+                                        "group" => "Group",
+                                        "friend" => "Friend",
+                                        "temp" => "Temp",
+                                        _ => abort!(
+                                            &value.span(),
+                                            "chat只支持 group friend temp, 多个用|分隔"
+                                        ),
+                                    });
+                                }
+                                children_args.push(EventArg::Chat(kinds));
+                            }
+                            _ => abort!(&ident.span(), "chat只支持字符串类型参数值"),
+                        },
                         _ => abort!(&ident.span(), "不支持的参数名称"),
                     }
                 }
@@ -145,8 +296,9 @@ pub(crate) fn arg_to_token(arg: EventArg) -> proc_macro2::TokenStream {
             }
         }
         EventArg::Regexp(string) => {
+            let regex = static_regex(&string);
             quote! {
-                ::proc_qq::EventArg::Regexp(#string .to_string())
+                ::proc_qq::EventArg::Regexp(#regex)
             }
         }
         EventArg::TrimEq(string) => {
@@ -155,8 +307,38 @@ pub(crate) fn arg_to_token(arg: EventArg) -> proc_macro2::TokenStream {
             }
         }
         EventArg::TrimRegexp(string) => {
+            let regex = static_regex(&string);
+            quote! {
+                ::proc_qq::EventArg::TrimRegexp(#regex)
+            }
+        }
+        EventArg::Group(uin_list) => {
+            quote! {
+                ::proc_qq::EventArg::Group(vec![#(#uin_list),*])
+            }
+        }
+        EventArg::User(uin_list) => {
+            quote! {
+                ::proc_qq::EventArg::User(vec![#(#uin_list),*])
+            }
+        }
+        EventArg::Chat(kinds) => {
+            let kinds = kinds
+                .iter()
+                .map(|kind| syn::Ident::new(kind, proc_macro2::Span::call_site()));
+            quote! {
+                ::proc_qq::EventArg::Chat(vec![#(::proc_qq::ChatKind::#kinds),*])
+            }
+        }
+        EventArg::AtBot => {
+            // 机器人的QQ号需要异步获取, 在handle中提前取出, 见 bot_uin_token
+            quote! {
+                ::proc_qq::EventArg::AtBot(__proc_qq_bot_uin)
+            }
+        }
+        EventArg::TargetIsBot => {
             quote! {
-                ::proc_qq::EventArg::TrimRegexp(#string .to_string())
+                ::proc_qq::EventArg::TargetIsBot(__proc_qq_bot_uin)
             }
         }
         EventArg::BotCommand(_) => {
@@ -165,6 +347,162 @@ pub(crate) fn arg_to_token(arg: EventArg) -> proc_macro2::TokenStream {
     }
 }
 
+// 包含at_bot或target_is_bot时, 在匹配之前获取机器人的QQ号
+pub(crate) fn bot_uin_token(
+    all: &[EventArg],
+    event: &syn::Pat,
+    is_message_event: bool,
+) -> proc_macro2::TokenStream {
+    if !contains_bot_uin(all) {
+        quote! {}
+    } else if is_message_event {
+        quote! {
+            let __proc_qq_bot_uin = ::proc_qq::ClientTrait::bot_uin(#event).await;
+        }
+    } else {
+        quote! {
+            let __proc_qq_bot_uin = ::proc_qq::ClientTrait::bot_uin(#event.client.as_ref()).await;
+        }
+    }
+}
+
+fn contains_bot_uin(all: &[EventArg]) -> bool {
+    all.iter().any(|x| match x {
+        EventArg::AtBot | EventArg::TargetIsBot => true,
+        EventArg::All(args) | EventArg::Any(args) | EventArg::Not(args) => contains_bot_uin(args),
+        _ => false,
+    })
+}
+
+// 是否有匹配消息内容或者at的条件, 只限制群/QQ号/会话类型时每个事件都会匹配
+pub(crate) fn contains_content_filter(all: &[EventArg]) -> bool {
+    all.iter().any(|x| match x {
+        EventArg::Group(_) | EventArg::User(_) | EventArg::Chat(_) => false,
+        EventArg::All(args) | EventArg::Any(args) | EventArg::Not(args) => {
+            contains_content_filter(args)
+        }
+        _ => true,
+    })
+}
+
+// 正则的命名分组作为handler的参数, #[event(regexp = "^禁言(?P<uin>\\d+)$")]
+pub(crate) struct CaptureRegexp<'a> {
+    pattern: String,
+    trim: bool,
+    params: Vec<(&'a syn::Ident, &'a syn::Type)>,
+}
+
+// 有多余的参数时, 从event参数中取出带有命名分组的正则, 这个正则改为在提取参数时匹配
+pub(crate) fn take_capture_regexp<'a>(
+    all: Vec<EventArg>,
+    params: &[&'a FnArg],
+) -> (Vec<EventArg>, Option<CaptureRegexp<'a>>) {
+    if params.is_empty() {
+        return (all, None);
+    }
+    let regexp_count = all
+        .iter()
+        .filter(|x| matches!(x, EventArg::Regexp(_) | EventArg::TrimRegexp(_)))
+        .count();
+    if regexp_count != 1 {
+        abort!(
+            &params[0].span(),
+            "使用正则的命名分组作为参数时, event中需要有且只有一个regexp或trim_regexp (不能在all any not中)"
+        );
+    }
+    let mut others = vec![];
+    let mut capture = None;
+    for x in all {
+        match x {
+            EventArg::Regexp(pattern) => capture = Some((pattern, false)),
+            EventArg::TrimRegexp(pattern) => capture = Some((pattern, true)),
+            x => others.push(x),
+        }
+    }
+    let (pattern, trim) = capture.unwrap();
+    // 编译期已经检查过
+    let regex = regex::Regex::new(pattern.as_str()).unwrap();
+    let names: Vec<&str> = regex.capture_names().flatten().collect();
+    let mut capture_params = vec![];
+    for param in params {
+        let pt = match param {
+            FnArg::Typed(pt) => pt,
+            FnArg::Receiver(_) => abort!(&param.span(), "不支持self"),
+        };
+        let ident = match pt.pat.as_ref() {
+            Pat::Ident(pi) => &pi.ident,
+            _ => abort!(&pt.pat.span(), "正则分组的参数必须是标识符"),
+        };
+        if !names.contains(&ident.to_string().as_str()) {
+            abort!(
+                &ident.span(),
+                "正则中没有名为 {} 的分组, 请使用 (?P<{}>...)",
+                ident,
+                ident
+            );
+        }
+        capture_params.push((ident, pt.ty.as_ref()));
+    }
+    (
+        others,
+        Some(CaptureRegexp {
+            pattern,
+            trim,
+            params: capture_params,
+        }),
+    )
+}
+
+impl CaptureRegexp<'_> {
+    // 匹配正则并且提取参数, 匹配失败或者参数转换失败时返回false
+    pub(crate) fn gets(&self, event: &Pat) -> proc_macro2::TokenStream {
+        let regex = static_regex(&self.pattern);
+        let content = if self.trim {
+            quote! {__proc_qq_content.trim()}
+        } else {
+            quote! {__proc_qq_content.as_str()}
+        };
+        let mut gets = quote! {
+            let __proc_qq_content = ::proc_qq::MessageContentTrait::message_content(#event);
+            let __proc_qq_captures = match #regex.captures(#content) {
+                Some(captures) => captures,
+                None => return Ok(false),
+            };
+        };
+        for (ident, ty) in &self.params {
+            let name = ident.to_string();
+            gets.append_all(quote! {
+                let #ident: #ty = match ::proc_qq::FromRegexCapture::from_capture(
+                    __proc_qq_captures.name(#name).map(|m| m.as_str()),
+                ) {
+                    Some(value) => value,
+                    None => return Ok(false),
+                };
+            });
+        }
+        gets
+    }
+
+    pub(crate) fn pats(&self) -> proc_macro2::TokenStream {
+        let idents = self.params.iter().map(|(ident, _)| ident);
+        quote! {#(#idents,)*}
+    }
+
+    pub(crate) fn params_in_raw(&self) -> proc_macro2::TokenStream {
+        let params = self.params.iter().map(|(ident, ty)| quote! {#ident: #ty});
+        quote! {#(#params,)*}
+    }
+}
+
+// 正则在编译期已经检查过, 运行时只编译一次
+fn static_regex(pattern: &str) -> proc_macro2::TokenStream {
+    quote! {{
+        static REGEX: ::std::sync::LazyLock<::proc_qq::re_exports::regex::Regex> =
+            ::std::sync::LazyLock::new(|| ::proc_qq::re_exports::regex::Regex::new(#pattern).unwrap());
+        &*REGEX
+    }}
+}
+
 pub(crate) fn args_to_token(all: Vec<EventArg>) -> proc_macro2::TokenStream {
     let mut args = quote! {};
     for arg in all {
@@ -201,6 +539,11 @@ pub(crate) fn contains_bot_command(all: &Vec<EventArg>) -> bool {
             EventArg::Eq(_) => {}
             EventArg::TrimRegexp(_) => {}
             EventArg::TrimEq(_) => {}
+            EventArg::Group(_) => {}
+            EventArg::User(_) => {}
+            EventArg::Chat(_) => {}
+            EventArg::AtBot => {}
+            EventArg::TargetIsBot => {}
         }
     }
     false
@@ -209,7 +552,7 @@ pub(crate) fn contains_bot_command(all: &Vec<EventArg>) -> bool {
 pub(crate) fn parse_args_and_command(
     method: &ItemFn,
     attrs: AttributeArgs,
-) -> (Vec<EventArg>, Option<String>) {
+) -> (Vec<EventArg>, Option<LitStr>) {
     // 从众多EventArg中找到bot_command（如果存在）
     let all: Vec<EventArg> = parse_args(attrs);
     let mut bot_command = None;
@@ -238,3 +581,26 @@ pub(crate) fn parse_args_and_command(
     }
     (all, bot_command)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::NOT_MESSAGE_EVENTS;
+
+    // NOT_MESSAGE_EVENTS 需要和运行时 hand_events! 中的事件保持一致
+    #[test]
+    fn not_message_events_match_runtime() {
+        let source = include_str!("../../proc_qq/src/handler/event_args.rs");
+        let table = source
+            .split("\nhand_events! {")
+            .nth(1)
+            .and_then(|rest| rest.split("\n}").next())
+            .expect("没有找到 hand_events! 表");
+        let runtime: Vec<String> = table
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| format!("& {}", line.split('(').next().unwrap()))
+            .collect();
+        assert_eq!(NOT_MESSAGE_EVENTS, runtime.as_slice());
+    }
+}
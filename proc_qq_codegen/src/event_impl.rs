@@ -0,0 +1,116 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Attribute, FnArg, ImplItem, ItemImpl, Pat, Type};
+
+// 生成的handler名称为 结构体名称_方法名称
+pub(crate) fn owner_ident(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => match path.path.segments.last() {
+            Some(segment) => segment.ident.to_string(),
+            None => abort!(&ty.span(), "event_impl只支持结构体"),
+        },
+        _ => abort!(&ty.span(), "event_impl只支持结构体"),
+    }
+}
+
+// #[event_impl] impl Handlers { #[event] async fn on_message(&self, event: &MessageEvent) ... }
+pub(crate) fn expand_event_impl(mut item_impl: ItemImpl) -> TokenStream {
+    if !item_impl.generics.params.is_empty() {
+        abort!(&item_impl.generics.span(), "event_impl不支持泛型");
+    }
+    if let Some((_, path, _)) = &item_impl.trait_ {
+        abort!(&path.span(), "event_impl只支持结构体自身的impl");
+    }
+    let owner = item_impl.self_ty.as_ref().clone();
+    let owner_name = owner_ident(&owner);
+    let mut events = quote! {};
+    let mut handlers = vec![];
+    let mut items = vec![];
+    for item in std::mem::take(&mut item_impl.items) {
+        let mut method = match item {
+            ImplItem::Method(method) => method,
+            item => {
+                items.push(item);
+                continue;
+            }
+        };
+        let position = match method.attrs.iter().position(is_event_attr) {
+            Some(position) => position,
+            None => {
+                items.push(ImplItem::Method(method));
+                continue;
+            }
+        };
+        let attr = method.attrs.remove(position);
+        match method.sig.inputs.first() {
+            Some(FnArg::Receiver(receiver))
+                if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+            _ => abort!(
+                &method.sig.span(),
+                "event_impl中的#[event]的第一个参数必须是&self"
+            ),
+        }
+        let args = if attr.tokens.is_empty() {
+            quote! {}
+        } else {
+            match attr.parse_args::<TokenStream>() {
+                Ok(args) => args,
+                Err(err) => abort!(err.span(), "{}", err),
+            }
+        };
+        let ident = syn::Ident::new(
+            &format!("{}_{}", owner_name, method.sig.ident),
+            method.sig.ident.span(),
+        );
+        handlers.push(quote! {#ident::new(self.clone()).into()});
+        // 方法保留在impl中, 生成的handler取出结构体后调用这个方法, 方法中的self和Self仍然是结构体
+        let method_ident = &method.sig.ident;
+        let call_args = method.sig.inputs.iter().skip(1).map(|arg| match arg {
+            FnArg::Typed(pt) => match pt.pat.as_ref() {
+                Pat::Ident(pat) => pat.ident.clone(),
+                _ => abort!(&pt.pat.span(), "event_impl中的#[event]的参数只支持变量名"),
+            },
+            FnArg::Receiver(_) => abort!(&arg.span(), "不支持self"),
+        });
+        let block = syn::parse_quote! {{
+            let this: &#owner = &self.inner;
+            <#owner>::#method_ident(this, #(#call_args),*).await
+        }};
+        let item_fn = syn::ItemFn {
+            attrs: method.attrs.clone(),
+            vis: method.vis.clone(),
+            sig: method.sig.clone(),
+            block: Box::new(block),
+        };
+        events.extend(crate::event_tokens(args, item_fn, Some(&owner)));
+        items.push(ImplItem::Method(method));
+    }
+    if handlers.is_empty() {
+        abort!(&item_impl.self_ty.span(), "event_impl中没有#[event]");
+    }
+    item_impl.items = items;
+    quote! {
+        #item_impl
+        #events
+        impl ::proc_qq::EventHandlers for #owner {
+            fn handlers(self: ::std::sync::Arc<Self>) -> Vec<::proc_qq::ModuleEventHandler> {
+                vec![#(#handlers),*]
+            }
+        }
+        impl ::proc_qq::IntoModuleEventHandlers for #owner {
+            fn into_handlers(self) -> Vec<::proc_qq::ModuleEventHandler> {
+                ::proc_qq::EventHandlers::handlers(::std::sync::Arc::new(self))
+            }
+        }
+    }
+}
+
+// #[event] 或 #[proc_qq::event]
+fn is_event_attr(attr: &Attribute) -> bool {
+    attr.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "event")
+}
@@ -0,0 +1,270 @@
+use proc_macro2::Span;
+use proc_macro_error::abort;
+use syn::spanned::Spanned;
+use syn::{AttributeArgs, Lit, LitStr, Meta, NestedMeta};
+
+/// event的选项, 与事件参数(event_args)不同, 不需要开启event_args特性
+pub(crate) struct EventOptions {
+    pub(crate) priority: i32,
+    // 毫秒
+    pub(crate) timeout: Option<u64>,
+    pub(crate) cooldown: Option<CooldownOption>,
+    pub(crate) require: Option<RequireOption>,
+    pub(crate) usage: Option<UsageOption>,
+}
+
+/// #[event(bot_command = "/ban {user} {time}", usage = true, usage_reply = "...")]
+#[cfg_attr(not(feature = "event_args"), allow(dead_code))]
+pub(crate) struct UsageOption {
+    pub(crate) span: Span,
+    // 为None时使用bot_command作为用法
+    pub(crate) usage: Option<String>,
+    pub(crate) reply: Option<String>,
+}
+
+/// #[event(require = "group_admin,bot_admin", require_reply = "...")]
+pub(crate) struct RequireOption {
+    pub(crate) span: Span,
+    // Require的成员名称, 需要全部满足
+    pub(crate) requires: Vec<&'static str>,
+    pub(crate) reply: Option<String>,
+}
+
+/// #[event(cooldown = "30s", per = "user", burst = 3, cooldown_reply = "...")]
+pub(crate) struct CooldownOption {
+    pub(crate) span: Span,
+    // 毫秒
+    pub(crate) period: u64,
+    // CooldownPer的成员名称
+    pub(crate) per: &'static str,
+    pub(crate) burst: u32,
+    pub(crate) reply: Option<String>,
+}
+
+// 从event的参数中取出选项, 返回剩余的参数
+pub(crate) fn take_event_options(attrs: AttributeArgs) -> (EventOptions, AttributeArgs) {
+    let mut options = EventOptions {
+        priority: 0,
+        timeout: None,
+        cooldown: None,
+        require: None,
+        usage: None,
+    };
+    let mut cooldown: Option<(Span, u64)> = None;
+    // 依赖cooldown的选项, 最后检查
+    let mut cooldown_args: Vec<(Span, &'static str)> = vec![];
+    let mut per = "User";
+    let mut burst = 1;
+    let mut reply = None;
+    let mut require_reply: Option<(Span, String)> = None;
+    let mut usage_reply: Option<(Span, String)> = None;
+    let mut others = vec![];
+    for nm in attrs {
+        if let NestedMeta::Meta(Meta::NameValue(nv)) = &nm {
+            let name = match nv.path.get_ident() {
+                Some(ident) => ident.to_string(),
+                None => {
+                    others.push(nm);
+                    continue;
+                }
+            };
+            match name.as_str() {
+                "priority" => {
+                    options.priority = match &nv.lit {
+                        Lit::Int(value) => match value.base10_parse::<i32>() {
+                            Ok(priority) => priority,
+                            Err(_) => abort!(&nv.lit.span(), "priority必须是i32类型的整数"),
+                        },
+                        _ => abort!(&nv.lit.span(), "priority只支持整数类型参数值"),
+                    };
+                }
+                "timeout" => {
+                    options.timeout = Some(lit_duration_millis(&nv.lit, "timeout"));
+                }
+                "cooldown" => {
+                    cooldown = Some((nv.lit.span(), lit_duration_millis(&nv.lit, "cooldown")));
+                }
+                "per" => {
+                    per = match lit_str(&nv.lit, "per").value().as_str() {
+                        "user" => "User",
+                        "group" => "Group",
+                        "global" => "Global",
+                        _ => abort!(&nv.lit.span(), "per只支持 user group global"),
+                    };
+                    cooldown_args.push((nv.path.span(), "per"));
+                }
+                "burst" => {
+                    burst = match &nv.lit {
+                        Lit::Int(value) => match value.base10_parse::<u32>() {
+                            Ok(burst) if burst > 0 => burst,
+                            _ => abort!(&nv.lit.span(), "burst必须是大于0的整数"),
+                        },
+                        _ => abort!(&nv.lit.span(), "burst只支持整数类型参数值"),
+                    };
+                    cooldown_args.push((nv.path.span(), "burst"));
+                }
+                "cooldown_reply" => {
+                    reply = Some(lit_str(&nv.lit, "cooldown_reply").value());
+                    cooldown_args.push((nv.path.span(), "cooldown_reply"));
+                }
+                "require" => {
+                    let value = lit_str(&nv.lit, "require").value();
+                    let mut requires = vec![];
+                    for name in value.split(',').map(str::trim) {
+                        requires.push(match name {
+                            "group_admin" => "GroupAdmin",
+                            "group_owner" => "GroupOwner",
+                            "bot_admin" => "BotAdmin",
+                            "superuser" => "Superuser",
+                            _ => abort!(
+                                &nv.lit.span(),
+                                "require只支持 group_admin group_owner bot_admin superuser, 多个权限用逗号分隔"
+                            ),
+                        });
+                    }
+                    options.require = Some(RequireOption {
+                        span: nv.lit.span(),
+                        requires,
+                        reply: None,
+                    });
+                }
+                "require_reply" => {
+                    require_reply =
+                        Some((nv.path.span(), lit_str(&nv.lit, "require_reply").value()));
+                }
+                "usage" => {
+                    options.usage = match &nv.lit {
+                        Lit::Bool(value) if value.value => Some(UsageOption {
+                            span: nv.lit.span(),
+                            usage: None,
+                            reply: None,
+                        }),
+                        Lit::Bool(_) => None,
+                        Lit::Str(value) => Some(UsageOption {
+                            span: nv.lit.span(),
+                            usage: Some(value.value()),
+                            reply: None,
+                        }),
+                        _ => abort!(&nv.lit.span(), "usage只支持bool或字符串类型参数值"),
+                    };
+                }
+                "usage_reply" => {
+                    usage_reply = Some((nv.path.span(), lit_str(&nv.lit, "usage_reply").value()));
+                }
+                _ => others.push(nm),
+            }
+            continue;
+        }
+        others.push(nm);
+    }
+    match cooldown {
+        Some((span, period)) => {
+            options.cooldown = Some(CooldownOption {
+                span,
+                period,
+                per,
+                burst,
+                reply,
+            })
+        }
+        None => {
+            if let Some((span, name)) = cooldown_args.first() {
+                abort!(span, "{}需要配合cooldown使用", name);
+            }
+        }
+    }
+    if let Some((span, reply)) = require_reply {
+        match &mut options.require {
+            Some(require) => require.reply = Some(reply),
+            None => abort!(span, "require_reply需要配合require使用"),
+        }
+    }
+    if let Some((span, reply)) = usage_reply {
+        match &mut options.usage {
+            Some(usage) => usage.reply = Some(reply),
+            None => abort!(span, "usage_reply需要配合usage使用"),
+        }
+    }
+    (options, others)
+}
+
+fn lit_str<'a>(lit: &'a Lit, name: &str) -> &'a LitStr {
+    match lit {
+        Lit::Str(value) => value,
+        _ => abort!(&lit.span(), "{}只支持字符串类型参数值", name),
+    }
+}
+
+fn lit_duration_millis(lit: &Lit, name: &str) -> u64 {
+    match parse_duration_millis(&lit_str(lit, name).value()) {
+        Some(millis) if millis > 0 => millis,
+        _ => abort!(
+            &lit.span(),
+            "{}格式错误, 例如 \"500ms\" \"10s\" \"1m30s\" \"10分钟\"",
+            name
+        ),
+    }
+}
+
+// 解析 "500ms" "10s" "1m30s" "10分钟" 这样的时长, 返回毫秒, 只有数字时单位为秒.
+// 和运行时的 proc_qq::parse_duration 使用相同的格式
+pub(crate) fn parse_duration_millis(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Ok(secs) = text.parse::<u64>() {
+        return secs.checked_mul(1000);
+    }
+    if text.is_empty() {
+        return None;
+    }
+    let mut total: u64 = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let number = rest[..digits].parse::<u64>().ok()?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let millis = match rest[..unit].to_ascii_lowercase().as_str() {
+            "ms" | "毫秒" => 1,
+            "s" | "sec" | "秒" | "秒钟" => 1000,
+            "m" | "min" | "分" | "分钟" => 60 * 1000,
+            "h" | "时" | "小时" | "个小时" => 60 * 60 * 1000,
+            "d" | "天" | "日" => 24 * 60 * 60 * 1000,
+            "w" | "周" | "星期" => 7 * 24 * 60 * 60 * 1000,
+            _ => return None,
+        };
+        total = total.checked_add(number.checked_mul(millis)?)?;
+        rest = &rest[unit..];
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration_millis;
+
+    // 和 proc_qq 中 parse_duration 的测试使用相同的例子
+    #[test]
+    fn parse_duration_millis_grammar() {
+        assert_eq!(parse_duration_millis("90"), Some(90_000));
+        assert_eq!(parse_duration_millis("500ms"), Some(500));
+        assert_eq!(parse_duration_millis("1h30m"), Some(90 * 60_000));
+        assert_eq!(parse_duration_millis("10分钟"), Some(10 * 60_000));
+        assert_eq!(parse_duration_millis("1天12小时"), Some(36 * 60 * 60_000));
+        assert_eq!(parse_duration_millis("1W"), Some(7 * 24 * 60 * 60_000));
+        assert_eq!(parse_duration_millis(""), None);
+        assert_eq!(parse_duration_millis("m"), None);
+        assert_eq!(parse_duration_millis("10x"), None);
+        assert_eq!(parse_duration_millis("99999999999999999999s"), None);
+        assert_eq!(
+            parse_duration_millis(&format!("{}s", u64::MAX / 1000 + 1)),
+            None
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Attribute, Lit, Meta};
+
+#[cfg(feature = "event_args")]
+use crate::event_arg::EventArg;
+
+// 文档注释作为handler的说明, 多行时保留换行
+pub(crate) fn doc_description(attrs: &[Attribute]) -> String {
+    let mut lines = vec![];
+    for attr in attrs {
+        if !attr.path.is_ident("doc") {
+            continue;
+        }
+        if let Ok(Meta::NameValue(nv)) = attr.parse_meta() {
+            if let Lit::Str(value) = nv.lit {
+                lines.push(value.value().trim().to_string());
+            }
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+// 直接写在event括号中的eq和trim_eq也作为指令显示
+#[cfg(feature = "event_args")]
+pub(crate) fn eq_commands(all: &[EventArg]) -> Vec<String> {
+    all.iter()
+        .filter_map(|arg| match arg {
+            EventArg::Eq(value) | EventArg::TrimEq(value) => Some(value.trim().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+// 生成 ::proc_qq::HandlerHelp
+pub(crate) fn help_token(
+    commands: &[String],
+    description: &str,
+    requires: &[&'static str],
+) -> TokenStream {
+    let requires = requires
+        .iter()
+        .map(|name| syn::Ident::new(name, Span::call_site()));
+    quote! {
+        ::proc_qq::HandlerHelp {
+            commands: vec![#(#commands.to_owned()),*],
+            description: #description.to_owned(),
+            requires: vec![#(::proc_qq::Require::#requires),*],
+        }
+    }
+}
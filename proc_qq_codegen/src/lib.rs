@@ -3,20 +3,38 @@ use proc_macro::TokenStream;
 use proc_macro2::Span;
 use proc_macro_error::{abort, proc_macro_error};
 use quote::{quote, ToTokens, TokenStreamExt};
-use syn::parse::{Parse, ParseStream};
+use syn::parse::{Parse, ParseStream, Parser};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{parse_macro_input, Expr, FnArg, Meta, NestedMeta, PatType, Token};
 
+use crate::auto_module::*;
 #[cfg(feature = "event_args")]
 use crate::bot_command::*;
 #[cfg(feature = "event_args")]
+use crate::command_arg::*;
+#[cfg(feature = "event_args")]
+use crate::command_group::*;
+use crate::data_param::*;
+#[cfg(feature = "event_args")]
 use crate::event_arg::*;
+use crate::event_impl::*;
+use crate::event_option::*;
+use crate::help::*;
 
+mod auto_module;
 #[cfg(feature = "event_args")]
 mod bot_command;
 #[cfg(feature = "event_args")]
+mod command_arg;
+#[cfg(feature = "event_args")]
+mod command_group;
+mod data_param;
+#[cfg(feature = "event_args")]
 mod event_arg;
+mod event_impl;
+mod event_option;
+mod help;
 
 /// 如果设置PROC_QQ_CODEGEN_DEBUG变量，编译时将会以note方式打印PROC_QQ_CODEGEN的生成结果
 
@@ -42,8 +60,26 @@ macro_rules! emit {
 pub fn event(args: TokenStream, input: TokenStream) -> TokenStream {
     // 获取方法
     let method = parse_macro_input!(input as syn::ItemFn);
+    emit!(event_tokens(args.into(), method, None))
+}
+
+// owner为 #[event_impl] 修饰的结构体, 生成的handler持有结构体的Arc, method的block为调用结构体方法的代码
+fn event_tokens(
+    args: proc_macro2::TokenStream,
+    method: syn::ItemFn,
+    owner: Option<&syn::Type>,
+) -> proc_macro2::TokenStream {
+    #[cfg(feature = "event_args")]
+    let args = bracket_list_to_paren(args);
+    // 取出不依赖event_args的选项
+    let attrs: syn::AttributeArgs =
+        match Punctuated::<NestedMeta, Token![,]>::parse_terminated.parse2(args) {
+            Ok(attrs) => attrs.into_iter().collect(),
+            Err(err) => return err.to_compile_error(),
+        };
+    let (options, attrs) = take_event_options(attrs);
     #[cfg(not(feature = "event_args"))]
-    if !args.is_empty() {
+    if !attrs.is_empty() {
         abort!(&method.span(), "event参数请配合event_args特性使用");
     }
     // 判断是否为async方法
@@ -76,31 +112,190 @@ pub fn event(args: TokenStream, input: TokenStream) -> TokenStream {
     let param_ty = event_param.ty.as_ref();
     let param_ty = quote! {#param_ty};
     let (trait_name, enum_name) = struct_name(event_param, param_ty.to_string());
+    // Data<T>参数从共享数据中获取, 不参与指令匹配
+    let (data_lets, other_params) = take_data_params(&params[param_skip..params.len()]);
     // event过程宏的的参数机型匹配
     #[cfg(feature = "event_args")]
-    let attrs = parse_macro_input!(args as syn::AttributeArgs);
-    #[cfg(feature = "event_args")]
     let (all_filter_without_bot_command, bot_command) = parse_args_and_command(&method, attrs);
+    // 指令的用法, 默认为去掉多余空白的bot_command
     #[cfg(feature = "event_args")]
-    let command_items = parse_bot_command(&method, bot_command);
+    let usage_pattern = bot_command.as_ref().map(|command| {
+        command
+            .value()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
     #[cfg(feature = "event_args")]
-    let bot_args = parse_bot_args(&method, &params[param_skip..params.len()], command_items);
+    if let (Some(usage), None) = (&options.usage, &bot_command) {
+        abort!(&usage.span, "usage需要配合bot_command使用");
+    }
+    // 帮助中显示的指令, 设置了usage时使用usage
+    #[allow(unused_mut)]
+    let mut help_commands: Vec<String> = vec![];
+    #[cfg(feature = "event_args")]
+    if let Some(command) = options
+        .usage
+        .as_ref()
+        .and_then(|usage| usage.usage.clone())
+        .or_else(|| usage_pattern.clone())
+    {
+        help_commands.push(command);
+    }
+    #[cfg(feature = "event_args")]
+    help_commands.extend(eq_commands(&all_filter_without_bot_command));
+    let help = help_token(
+        &help_commands,
+        &doc_description(&method.attrs),
+        options
+            .require
+            .as_ref()
+            .map(|require| require.requires.as_slice())
+            .unwrap_or_default(),
+    );
+    #[cfg(feature = "event_args")]
+    let command_items = parse_bot_command(bot_command);
+    #[cfg(feature = "event_args")]
+    let bot_args = parse_bot_args(&method, &other_params, command_items);
+    #[cfg(not(feature = "event_args"))]
+    if let Some(usage) = &options.usage {
+        abort!(
+            &usage.span,
+            "usage需要配合bot_command使用, 请开启event_args特性"
+        );
+    }
     #[cfg(not(feature = "event_args"))]
-    if params.len() > param_skip {
+    if !other_params.is_empty() {
         abort!(
-            &params[param_skip].span(),
+            &other_params[0].span(),
             "不支持更多的参数，请配合event_args特性使用"
         );
     }
+    let is_message_event = matches!(
+        param_ty.to_string().as_str(),
+        "& MessageEvent"
+            | "& GroupMessageEvent"
+            | "& FriendMessageEvent"
+            | "& GroupTempMessageEvent"
+    );
+    // 冷却, 在权限之后检查, 没有权限的调用不消耗次数.
+    // #[event_impl] 的handler中冷却放在结构体中, 每个实例单独计算
+    let cooldown_new = match &options.cooldown {
+        Some(cooldown) => {
+            if !is_message_event {
+                abort!(
+                    &cooldown.span,
+                    "cooldown只支持消息类型事件 (MessageEvent,*MessageEvent)"
+                );
+            }
+            let period = cooldown.period;
+            let per = syn::Ident::new(cooldown.per, Span::call_site());
+            let burst = cooldown.burst;
+            let reply = match &cooldown.reply {
+                Some(reply) => quote! {Some(#reply)},
+                None => quote! {None},
+            };
+            Some(quote! {
+                ::proc_qq::Cooldown::new(
+                    ::std::time::Duration::from_millis(#period),
+                    #burst,
+                    ::proc_qq::CooldownPer::#per,
+                    #reply,
+                )
+            })
+        }
+        None => None,
+    };
+    let cooldown_check = match (&cooldown_new, owner) {
+        (Some(cooldown_new), None) => quote! {
+            static COOLDOWN: ::proc_qq::Cooldown = #cooldown_new;
+            COOLDOWN.acquire(#param_pat).await?;
+        },
+        (Some(_), Some(_)) => quote! {
+            self.cooldown.acquire(#param_pat).await?;
+        },
+        (None, _) => quote! {},
+    };
     // struct
-    let ident = &method.sig.ident;
-    let ident_str = format!("{}", ident);
-    let build_struct = quote! {
-        #[allow(non_camel_case_types)]
-        pub struct #ident {}
+    let ident_str = format!("{}", method.sig.ident);
+    let (ident, build_struct) = match owner {
+        None => {
+            let ident = method.sig.ident.clone();
+            let build_struct = quote! {
+                #[allow(non_camel_case_types)]
+                pub struct #ident {}
+            };
+            (ident, build_struct)
+        }
+        Some(owner) => {
+            let ident = syn::Ident::new(
+                &format!("{}_{}", owner_ident(owner), method.sig.ident),
+                method.sig.ident.span(),
+            );
+            // 只在 EventHandlers::handlers 中构造, 不设置pub, 结构体不是pub时也可以使用
+            let build_struct = match &cooldown_new {
+                Some(cooldown_new) => quote! {
+                    #[allow(non_camel_case_types)]
+                    struct #ident {
+                        inner: ::std::sync::Arc<#owner>,
+                        cooldown: ::proc_qq::Cooldown,
+                    }
+                    impl #ident {
+                        fn new(inner: ::std::sync::Arc<#owner>) -> Self {
+                            Self {
+                                inner,
+                                cooldown: #cooldown_new,
+                            }
+                        }
+                    }
+                },
+                None => quote! {
+                    #[allow(non_camel_case_types)]
+                    struct #ident {
+                        inner: ::std::sync::Arc<#owner>,
+                    }
+                    impl #ident {
+                        fn new(inner: ::std::sync::Arc<#owner>) -> Self {
+                            Self { inner }
+                        }
+                    }
+                },
+            };
+            (ident, build_struct)
+        }
+    };
+    let ident = &ident;
+    // 权限, 在指令匹配之后检查
+    let require_check = match &options.require {
+        Some(require) => {
+            if !is_message_event {
+                abort!(
+                    &require.span,
+                    "require只支持消息类型事件 (MessageEvent,*MessageEvent)"
+                );
+            }
+            let requires = require
+                .requires
+                .iter()
+                .map(|name| syn::Ident::new(name, Span::call_site()));
+            let reply = match &require.reply {
+                Some(reply) => quote! {Some(#reply)},
+                None => quote! {None},
+            };
+            quote! {
+                ::proc_qq::check_require(#param_pat, &[#(::proc_qq::Require::#requires),*], #reply).await?;
+            }
+        }
+        None => quote! {},
     };
     // trait
     let block = &method.block;
+    let block = quote! {{
+        #require_check
+        #cooldown_check
+        #data_lets
+        #block
+    }};
     #[cfg(not(feature = "event_args"))]
     let build_trait = quote! {
         #[::proc_qq::re_exports::async_trait::async_trait]
@@ -108,8 +303,38 @@ pub fn event(args: TokenStream, input: TokenStream) -> TokenStream {
             async fn handle(&self, #param_pat: #param_ty) -> ::proc_qq::re_exports::anyhow::Result<bool> #block
         }
     };
+    // 没有bot_command时, 多余的参数从正则的命名分组中获取
+    #[cfg(feature = "event_args")]
+    let (all_filter_without_bot_command, capture_regexp) = if bot_args.is_none() {
+        take_capture_regexp(all_filter_without_bot_command, &other_params)
+    } else {
+        (all_filter_without_bot_command, None)
+    };
+    // 冷却在匹配之后检查, 没有匹配条件时每个事件都会消耗次数, 被限流后还会拦截之后的模块
     #[cfg(feature = "event_args")]
-    let build_trait = if all_filter_without_bot_command.is_empty() && bot_args.is_none() {
+    if let Some(cooldown) = &options.cooldown {
+        if bot_args.is_none()
+            && capture_regexp.is_none()
+            && !contains_content_filter(&all_filter_without_bot_command)
+        {
+            abort!(
+                &cooldown.span,
+                "cooldown需要配合bot_command, regexp, eq等匹配条件使用"
+            );
+        }
+    }
+    #[cfg(not(feature = "event_args"))]
+    if let Some(cooldown) = &options.cooldown {
+        abort!(
+            &cooldown.span,
+            "cooldown需要配合bot_command, regexp, eq等匹配条件使用, 请开启event_args特性"
+        );
+    }
+    #[cfg(feature = "event_args")]
+    let build_trait = if all_filter_without_bot_command.is_empty()
+        && bot_args.is_none()
+        && capture_regexp.is_none()
+    {
         quote! {
             #[::proc_qq::re_exports::async_trait::async_trait]
             impl #trait_name for #ident {
@@ -117,181 +342,335 @@ pub fn event(args: TokenStream, input: TokenStream) -> TokenStream {
             }
         }
     } else {
-        match param_ty.to_string().as_str() {
-            "& MessageEvent" => (),
-            "& GroupMessageEvent" => (),
-            "& FriendMessageEvent" => (),
-            "& GroupTempMessageEvent" => (),
-            _ => abort!(
+        if !is_message_event && !is_not_message_event(param_ty.to_string().as_str()) {
+            abort!(&method.sig.span(), "event 的参数不支持这个类型的事件");
+        }
+        if !is_message_event && bot_args.is_some() {
+            abort!(
+                &method.sig.span(),
+                "bot_command只支持消息类型事件 (MessageEvent,*MessageEvent)"
+            );
+        }
+        if !is_message_event && capture_regexp.is_some() {
+            abort!(
                 &method.sig.span(),
-                "event 的参数只支持消息类型事件 (MessageEvent,*MessageEvent)"
-            ),
+                "regexp只支持消息类型事件 (MessageEvent,*MessageEvent)"
+            );
         }
+        check_args_for_event(&method, &all_filter_without_bot_command, is_message_event);
+        let bot_uin_let =
+            bot_uin_token(&all_filter_without_bot_command, param_pat, is_message_event);
         let args_vec = args_to_token(all_filter_without_bot_command);
-        if bot_args.is_none() {
+        if let Some(capture_regexp) = capture_regexp {
+            let gets = capture_regexp.gets(param_pat);
+            let p_pats = capture_regexp.pats();
+            let capture_params_in_raw = capture_regexp.params_in_raw();
             quote! {
                 #[::proc_qq::re_exports::async_trait::async_trait]
                 impl #trait_name for #ident {
                     async fn handle(&self, #param_pat: #param_ty) -> ::proc_qq::re_exports::anyhow::Result<bool> {
+                        #bot_uin_let
                         if !::proc_qq::match_event_args_all(#args_vec, #param_pat.into())? {
                             return Ok(false);
                         }
-                        self.raw(#param_pat).await
+                        #gets
+                        self.raw(#param_pat, #p_pats).await
                     }
                 }
                 impl #ident {
-                    async fn raw(&self, #param_pat: #param_ty) -> ::proc_qq::re_exports::anyhow::Result<bool> #block
+                    async fn raw(&self, #param_pat: #param_ty, #capture_params_in_raw) -> ::proc_qq::re_exports::anyhow::Result<bool> #block
                 }
             }
         } else {
-            let mut p_pats = quote! {};
-            let mut command_params_in_raw = quote! {};
-            let mut gets = quote! {};
-            for x in bot_args.unwrap() {
-                match x {
-                    BotParamsMather::Command(command) => {
-                        gets.append_all(quote! {
-                            if !matcher.match_command(#command) {
-                                return Ok(false);
+            match bot_args {
+                None => {
+                    quote! {
+                        #[::proc_qq::re_exports::async_trait::async_trait]
+                        impl #trait_name for #ident {
+                            async fn handle(&self, #param_pat: #param_ty) -> ::proc_qq::re_exports::anyhow::Result<bool> {
+                                #bot_uin_let
+                                if !::proc_qq::match_event_args_all(#args_vec, #param_pat.into())? {
+                                    return Ok(false);
+                                }
+                                self.raw(#param_pat).await
                             }
-                        });
+                        }
+                        impl #ident {
+                            async fn raw(&self, #param_pat: #param_ty) -> ::proc_qq::re_exports::anyhow::Result<bool> #block
+                        }
                     }
-                    BotParamsMather::Params(pat, ty) => {
-                        p_pats.append_all(quote! {
-                           #pat,
-                        });
-                        command_params_in_raw.append_all(quote! {
-                           #pat: #ty,
-                        });
-                        gets.append_all(quote! {
-                            let #pat: #ty = match ::proc_qq::matcher_get::<#ty>(&mut matcher) {
-                                Some(value) => value,
-                                None => return Ok(false),
+                }
+                Some(bot_args) => {
+                    let mut p_pats = quote! {};
+                    let mut command_params_in_raw = quote! {};
+                    let mut gets = quote! {};
+                    // 开头的固定字符串为指令的关键字, 关键字匹配之后参数不正确时回复用法
+                    let keyword_mismatch = quote! { return Ok(false) };
+                    let args_mismatch = match &options.usage {
+                        Some(usage) => {
+                            if !matches!(bot_args.first(), Some(BotParamsMather::Command(_))) {
+                                abort!(&usage.span, "使用usage时bot_command必须以固定字符串开头");
+                            }
+                            let text = match &usage.usage {
+                                Some(text) => text.clone(),
+                                None => usage_pattern.clone().unwrap(),
                             };
-                        });
-                    }
-                    BotParamsMather::Enum(pat, ty, values) => {
-                        p_pats.append_all(quote! {
-                           #pat,
-                        });
-                        command_params_in_raw.append_all(quote! {
-                           #pat: #ty,
-                        });
-                        let mut mmc = quote! {};
-                        for x in values {
-                            mmc.append_all(quote! {#x,});
+                            let reply = usage.reply.as_deref().unwrap_or("用法 : {usage}");
+                            quote! { return ::proc_qq::reply_usage(#param_pat, #text, #reply).await }
                         }
-                        gets.append_all(quote! {
-                            let #pat: #ty = match ::proc_qq::matcher_get_enum::<#ty>(&mut matcher, vec![#mmc]) {
-                                Some(value) => value,
-                                None => return Ok(false),
-                            };
-                        });
+                        None => keyword_mismatch.clone(),
+                    };
+                    // flag可以出现在任意参数之前, 在读取每个参数之前取出
+                    let mut flag_specs = quote! {};
+                    let mut flag_arms = quote! {};
+                    let mut flag_idx = 0usize;
+                    for x in &bot_args {
+                        match x {
+                            BotParamsMather::Flag(pat, ty, names) => {
+                                gets.append_all(quote! {
+                                    let mut #pat: #ty = false;
+                                });
+                                flag_specs.append_all(quote! {
+                                    (&[#(#names),*], false),
+                                });
+                                flag_arms.append_all(quote! {
+                                    Some(#flag_idx) => #pat = true,
+                                });
+                            }
+                            BotParamsMather::ValueFlag(pat, ty, inner, names) => {
+                                gets.append_all(quote! {
+                                    let mut #pat: #ty = None;
+                                });
+                                flag_specs.append_all(quote! {
+                                    (&[#(#names),*], true),
+                                });
+                                flag_arms.append_all(quote! {
+                                    Some(#flag_idx) => {
+                                        #pat = match ::proc_qq::matcher_get::<#inner>(&mut matcher) {
+                                            Some(value) => Some(value),
+                                            None => #args_mismatch,
+                                        };
+                                    }
+                                });
+                            }
+                            _ => continue,
+                        }
+                        flag_idx += 1;
                     }
-                    BotParamsMather::Multiple(multiple) => {
-                        //////////////////////////////////////////////
-                        gets.append_all(quote! {
-                            let mut ps = if let Some(ps) = matcher.tuple_matcher() {
-                                ps
-                            } else {
-                                return Ok(false);
-                            };
-                        });
-                        // let mut tp_elements = quote! {};
-                        for x in &multiple {
-                            match x {
-                                BotParamsMatherTuple::Command(name) => {
-                                    gets.append_all(quote! {
-                                        if !ps.match_command(#name) {
-                                            return Ok(false);
-                                        }
-                                    });
+                    let take_flags = if flag_idx == 0 {
+                        quote! {}
+                    } else {
+                        quote! {
+                            loop {
+                                match matcher.take_flag(&[#flag_specs]) {
+                                    #flag_arms
+                                    _ => break,
                                 }
-                                BotParamsMatherTuple::Params(pat, ty) => {
-                                    gets.append_all(quote! {
-                                        let #pat: #ty = if let Some(value) = ::proc_qq::tuple_matcher_get::<#ty>(&mut ps) {
-                                            value
-                                        } else {
-                                            return Ok(false);
-                                        };
-                                    });
-                                    p_pats.append_all(quote! {
-                                       #pat,
-                                    });
-                                    command_params_in_raw.append_all(quote! {
-                                       #pat: #ty,
-                                    });
+                            }
+                        }
+                    };
+                    let mut in_keyword = true;
+                    for x in bot_args {
+                        if !matches!(x, BotParamsMather::Command(_)) {
+                            in_keyword = false;
+                        }
+                        let mismatch = if in_keyword {
+                            &keyword_mismatch
+                        } else {
+                            &args_mismatch
+                        };
+                        match x {
+                            BotParamsMather::Command(commands) => {
+                                gets.append_all(quote! {
+                                    if !(#(matcher.match_command(#commands))||*) {
+                                        #mismatch
+                                    }
+                                });
+                            }
+                            BotParamsMather::Flag(pat, ty, _)
+                            | BotParamsMather::ValueFlag(pat, ty, _, _) => {
+                                p_pats.append_all(quote! {
+                                   #pat,
+                                });
+                                command_params_in_raw.append_all(quote! {
+                                   #pat: #ty,
+                                });
+                            }
+                            BotParamsMather::OptionalEnum(pat, ty, inner, values) => {
+                                p_pats.append_all(quote! {
+                                   #pat,
+                                });
+                                command_params_in_raw.append_all(quote! {
+                                   #pat: #ty,
+                                });
+                                gets.append_all(quote! {
+                                    #take_flags
+                                    let #pat: #ty = ::proc_qq::matcher_get_enum::<#inner>(&mut matcher, vec![#(#values),*]);
+                                });
+                            }
+                            BotParamsMather::Params(pat, ty) => {
+                                p_pats.append_all(quote! {
+                                   #pat,
+                                });
+                                command_params_in_raw.append_all(quote! {
+                                   #pat: #ty,
+                                });
+                                gets.append_all(quote! {
+                                    #take_flags
+                                    let #pat: #ty = match ::proc_qq::matcher_get::<#ty>(&mut matcher) {
+                                        Some(value) => value,
+                                        None => #mismatch,
+                                    };
+                                });
+                            }
+                            BotParamsMather::Enum(pat, ty, values) => {
+                                p_pats.append_all(quote! {
+                                   #pat,
+                                });
+                                command_params_in_raw.append_all(quote! {
+                                   #pat: #ty,
+                                });
+                                let mut mmc = quote! {};
+                                for x in values {
+                                    mmc.append_all(quote! {#x,});
                                 }
-                                BotParamsMatherTuple::Enum(pat, ty, values) => {
-                                    let mut mmc = quote! {};
-                                    for x in values {
-                                        mmc.append_all(quote! {#x,});
+                                gets.append_all(quote! {
+                                    #take_flags
+                                    let #pat: #ty = match ::proc_qq::matcher_get_enum::<#ty>(&mut matcher, vec![#mmc]) {
+                                        Some(value) => value,
+                                        None => #mismatch,
+                                    };
+                                });
+                            }
+                            BotParamsMather::Rest(pat, ty) => {
+                                p_pats.append_all(quote! {
+                                   #pat,
+                                });
+                                command_params_in_raw.append_all(quote! {
+                                   #pat: #ty,
+                                });
+                                gets.append_all(quote! {
+                                    #take_flags
+                                    let #pat: #ty = match ::proc_qq::matcher_get_rest::<#ty>(&mut matcher) {
+                                        Some(value) => value,
+                                        None => #mismatch,
+                                    };
+                                });
+                            }
+                            BotParamsMather::Multiple(multiple) => {
+                                //////////////////////////////////////////////
+                                gets.append_all(quote! {
+                                    #take_flags
+                                    let mut ps = if let Some(ps) = matcher.tuple_matcher() {
+                                        ps
+                                    } else {
+                                        #mismatch
+                                    };
+                                });
+                                // let mut tp_elements = quote! {};
+                                for x in &multiple {
+                                    match x {
+                                        BotParamsMatherTuple::Command(name) => {
+                                            gets.append_all(quote! {
+                                                if !ps.match_command(#name) {
+                                                    #mismatch
+                                                }
+                                            });
+                                        }
+                                        BotParamsMatherTuple::Params(pat, ty) => {
+                                            gets.append_all(quote! {
+                                                let #pat: #ty = if let Some(value) = ::proc_qq::tuple_matcher_get::<#ty>(&mut ps) {
+                                                    value
+                                                } else {
+                                                    #mismatch
+                                                };
+                                            });
+                                            p_pats.append_all(quote! {
+                                               #pat,
+                                            });
+                                            command_params_in_raw.append_all(quote! {
+                                               #pat: #ty,
+                                            });
+                                        }
+                                        BotParamsMatherTuple::Enum(pat, ty, values) => {
+                                            let mut mmc = quote! {};
+                                            for x in values {
+                                                mmc.append_all(quote! {#x,});
+                                            }
+                                            gets.append_all(quote! {
+                                                let #pat: #ty = if let Some(value) = ::proc_qq::tuple_matcher_get_enum::<#ty>(&mut ps, vec![#mmc]) {
+                                                    value
+                                                } else {
+                                                    #mismatch
+                                                };
+                                            });
+                                            p_pats.append_all(quote! {
+                                               #pat,
+                                            });
+                                            command_params_in_raw.append_all(quote! {
+                                               #pat: #ty,
+                                            });
+                                        }
                                     }
-                                    gets.append_all(quote! {
-                                        let #pat: #ty = if let Some(value) = ::proc_qq::tuple_matcher_get_enum::<#ty>(&mut ps, vec![#mmc]) {
-                                            value
-                                        } else {
-                                            return Ok(false);
-                                        };
-                                    });
-                                    p_pats.append_all(quote! {
-                                       #pat,
-                                    });
-                                    command_params_in_raw.append_all(quote! {
-                                       #pat: #ty,
-                                    });
                                 }
+                                ////////////////////////////////
                             }
                         }
-                        ////////////////////////////////
                     }
-                }
-            }
-            quote! {
-                #[::proc_qq::re_exports::async_trait::async_trait]
-                impl #trait_name for #ident {
-                    async fn handle(&self, #param_pat: #param_ty) -> ::proc_qq::re_exports::anyhow::Result<bool> {
-                        if !::proc_qq::match_event_args_all(#args_vec, #param_pat.into())? {
-                            return Ok(false);
+                    gets.append_all(take_flags);
+                    quote! {
+                        #[::proc_qq::re_exports::async_trait::async_trait]
+                        impl #trait_name for #ident {
+                            async fn handle(&self, #param_pat: #param_ty) -> ::proc_qq::re_exports::anyhow::Result<bool> {
+                                #bot_uin_let
+                                if !::proc_qq::match_event_args_all(#args_vec, #param_pat.into())? {
+                                    return Ok(false);
+                                }
+                                // 匹配指令是否能对应
+                                use ::proc_qq::MessageChainPointTrait;
+                                let mut matcher = ::proc_qq::CommandMatcher::from_message_chain(#param_pat.message_chain());
+                                if !matcher.strip_prefix(#param_pat).await {
+                                    #keyword_mismatch;
+                                }
+                                #gets
+                                if matcher.not_blank() {
+                                    #args_mismatch;
+                                }
+                                self.raw(#param_pat, #p_pats).await
+                            }
                         }
-                        // 匹配指令是否能对应
-                        use ::proc_qq::MessageChainPointTrait;
-                        let m_chan = #param_pat.message_chain().clone();
-                        let mut m_vec = vec![];
-                        for x in m_chan {
-                            m_vec.push(x);
+                        impl #ident {
+                            async fn raw(&self, #param_pat: #param_ty, #command_params_in_raw) -> ::proc_qq::re_exports::anyhow::Result<bool> #block
                         }
-                        let mut matcher = ::proc_qq::CommandMatcher::new(m_vec);
-                        #gets
-                        if matcher.not_blank() {
-                            return Ok(false);
-                        }
-                        self.raw(#param_pat, #p_pats).await
                     }
                 }
-                impl #ident {
-                    async fn raw(&self, #param_pat: #param_ty, #command_params_in_raw) -> ::proc_qq::re_exports::anyhow::Result<bool> #block
-                }
             }
         }
     };
     // into
+    let priority = options.priority;
+    let timeout = match options.timeout {
+        Some(timeout) => quote! {Some(::std::time::Duration::from_millis(#timeout))},
+        None => quote! {None},
+    };
     let build_into = quote! {
         impl Into<::proc_qq::ModuleEventHandler> for #ident {
             fn into(self) -> ::proc_qq::ModuleEventHandler {
                 ::proc_qq::ModuleEventHandler{
                     name: #ident_str.into(),
+                    priority: #priority,
+                    timeout: #timeout,
+                    help: #help,
                     process: #enum_name(Box::new(self)),
                 }
             }
         }
     };
-    // emit
-    emit!(quote! {
+    quote! {
         #build_struct
         #build_trait
         #build_into
-    })
+    }
 }
 
 fn struct_name(
@@ -383,28 +762,28 @@ fn struct_name(
             quote! {::proc_qq::MemberPermissionChangeEventProcess},
             quote! {::proc_qq::ModuleEventProcess::MemberPermissionChange},
         ),
-        "& NewMemberEventEvent" => (
-            quote! {::proc_qq::NewMemberEventEventProcess},
-            quote! {::proc_qq::ModuleEventProcess::NewMemberEvent},
+        "& NewMemberEvent" => (
+            quote! {::proc_qq::NewMemberEventProcess},
+            quote! {::proc_qq::ModuleEventProcess::NewMember},
         ),
-        "& SelfInvitedEventEvent" => (
-            quote! {::proc_qq::SelfInvitedEventEventProcess},
-            quote! {::proc_qq::ModuleEventProcess::SelfInvitedEvent},
+        "& SelfInvitedEvent" => (
+            quote! {::proc_qq::SelfInvitedEventProcess},
+            quote! {::proc_qq::ModuleEventProcess::SelfInvited},
         ),
         "& GroupAudioMessageEvent" => (
             quote! {::proc_qq::GroupAudioMessageEventProcess},
-            quote! {::proc_qq::ModuleEventProcess::GroupAudioMessageEvent},
+            quote! {::proc_qq::ModuleEventProcess::GroupAudioMessage},
         ),
         "& FriendAudioMessageEvent" => (
             quote! {::proc_qq::FriendAudioMessageEventProcess},
-            quote! {::proc_qq::ModuleEventProcess::FriendAudioMessageEvent},
+            quote! {::proc_qq::ModuleEventProcess::FriendAudioMessage},
         ),
         "& ClientDisconnect" => (
             quote! {::proc_qq::ClientDisconnectProcess},
             quote! {::proc_qq::ModuleEventProcess::ClientDisconnect},
         ),
-        "& GroupPoke" => (
-            quote! {::proc_qq::GroupPokeProcess},
+        "& GroupPokeEvent" => (
+            quote! {::proc_qq::GroupPokeEventProcess},
             quote! {::proc_qq::ModuleEventProcess::GroupPoke},
         ),
         t => abort!(
@@ -422,8 +801,9 @@ pub fn result(_: TokenStream, input: TokenStream) -> TokenStream {
     if method.sig.asyncness.is_none() {
         abort!(&method.sig.span(), "必须是async方法");
     }
-    // params check
-    let params = &method.sig.inputs;
+    // params check, 除了Data<T>之外必须有且只能有1~2个参数
+    let params: Vec<&FnArg> = method.sig.inputs.iter().collect();
+    let (data_lets, params) = take_data_params(&params);
     if params.len() != 1 && params.len() != 2 {
         abort!(&method.sig.span(), "必须有且只能有1~2个参数");
     };
@@ -443,6 +823,10 @@ pub fn result(_: TokenStream, input: TokenStream) -> TokenStream {
         let ident = &method.sig.ident;
         let ident_str = format!("{}", ident);
         let block = &method.block;
+        let block = quote! {{
+            #data_lets
+            #block
+        }};
         return emit!(quote! {
             #[allow(non_camel_case_types)]
             pub struct #ident {}
@@ -491,21 +875,75 @@ pub fn module(input: TokenStream) -> TokenStream {
     }
     let id = syn::parse_str::<Expr>(&params.expressions[0]).expect("id 解析错误");
     let name = syn::parse_str::<Expr>(&params.expressions[1]).expect("name 解析错误");
+    let mut priority = quote! {0};
+    let mut description = quote! {""};
     let mut handle_builder = String::new();
     for i in 2..params.expressions.len() {
-        handle_builder.push_str(&format!("{} {{}}.into(),", params.expressions[i]));
+        // priority = 100 设置模块的优先级, description = "..." 设置模块的说明, 其他的参数为handler
+        if let Ok(Expr::Assign(assign)) = syn::parse_str::<Expr>(&params.expressions[i]) {
+            if let Expr::Path(path) = assign.left.as_ref() {
+                let right = assign.right.as_ref();
+                if path.path.is_ident("priority") {
+                    priority = quote! {#right};
+                    continue;
+                }
+                if path.path.is_ident("description") {
+                    description = quote! {#right};
+                    continue;
+                }
+            }
+            abort!(params.span, "不支持的参数 : {}", params.expressions[i]);
+        }
+        // 路径为#[event]生成的结构体, 其他表达式(#[event_impl] 结构体的实例等)直接使用,
+        // 变量需要加上括号, 例如 (counter) , 都通过IntoModuleEventHandlers转换
+        let handler = match syn::parse_str::<Expr>(&params.expressions[i]) {
+            Ok(Expr::Path(path)) if path.qself.is_none() => {
+                format!("{} {{}}", params.expressions[i])
+            }
+            Ok(Expr::Paren(paren)) => paren.expr.to_token_stream().to_string(),
+            _ => params.expressions[i].clone(),
+        };
+        handle_builder.push_str(&format!(
+            "handles.extend(::proc_qq::IntoModuleEventHandlers::into_handlers({}));",
+            handler
+        ));
     }
-    let handle_invoker =
-        syn::parse_str::<Expr>(&format!("vec![{handle_builder}]")).expect("handle invoker解析错误");
+    let handle_invoker = syn::parse_str::<syn::Block>(&format!(
+        "{{ #[allow(unused_mut)] let mut handles: Vec<::proc_qq::ModuleEventHandler> = vec![]; {handle_builder} handles }}"
+    ))
+    .expect("handle invoker解析错误");
     TokenStream::from(quote! {
         ::proc_qq::Module {
             id: #id.to_owned(),
             name: #name.to_owned(),
+            description: #description.to_owned(),
+            priority: #priority,
             handles: #handle_invoker,
         }
     })
 }
 
+/// 修饰内联的mod, 收集其中的 #[event] #[result] #[scheduler_job] (以及 #[command_group] 中的handler),
+/// 在mod中生成 module() , 有结果处理器时生成 result_handlers() , 有定时任务时生成 scheduler() .
+/// 不支持 #[event_impl] (需要结构体的实例, 请使用 module! 注册).
+/// module! 已经使用了module这个名称, 所以叫做auto_module
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn auto_module(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as syn::AttributeArgs);
+    let module = parse_macro_input!(input as syn::ItemMod);
+    emit!(expand_auto_module(args, module))
+}
+
+/// 修饰结构体的impl, 其中的 #[event] 方法可以通过self访问结构体的字段,
+/// 使用 module!("id", "name", Handlers::new(..)) 注册结构体的实例
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn event_impl(_: TokenStream, input: TokenStream) -> TokenStream {
+    let item_impl = parse_macro_input!(input as syn::ItemImpl);
+    emit!(expand_event_impl(item_impl))
+}
+
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn event_fn(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -575,7 +1013,17 @@ pub fn scheduler_job(args: TokenStream, input: TokenStream) -> TokenStream {
             FnArg::Typed(t) => t,
         },
     };
+    // 之后的参数只能是Data<T>
+    let other_params: Vec<&FnArg> = sig_params.iter().skip(1).collect();
+    let (data_lets, other_params) = take_data_params(&other_params);
+    if let Some(param) = other_params.first() {
+        abort!(&param.span(), "除了第一个参数之外只支持Data<T>类型的参数");
+    }
     let block = &method.block;
+    let block = quote! {{
+        #data_lets
+        #block
+    }};
     let mut time_type = quote! {};
     if let Some(nm) = attrs.get(0) {
         match nm.clone() {
@@ -671,3 +1119,26 @@ pub fn scheduler(input: TokenStream) -> TokenStream {
         }
     })
 }
+
+/// 自定义的指令参数类型, 可以在bot_command中使用
+/// 枚举按照变体名或 #[arg(alias = "开")] 匹配 (不区分大小写), 元组结构体使用内部类型的匹配
+#[cfg(feature = "event_args")]
+#[proc_macro_error]
+#[proc_macro_derive(CommandArg, attributes(arg))]
+pub fn command_arg(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    emit!(derive_command_arg(input))
+}
+
+/// 指令组, 组内 #[event] 的bot_command前面会加上组的名称 (name),
+/// 除name和description之外的参数会添加到组内的每个 #[event] 上, require与event的require合并, 冷却不能在event中修改.
+/// 同时在组内生成名为 组名_help 的handler (例如admin::admin_help), 只发送组的名称时回复组内的指令
+#[cfg(feature = "event_args")]
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn command_group(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args: TokenStream = bracket_list_to_paren(args.into()).into();
+    let args = parse_macro_input!(args as syn::AttributeArgs);
+    let module = parse_macro_input!(input as syn::ItemMod);
+    emit!(expand_command_group(args, module))
+}
@@ -0,0 +1,50 @@
+use proc_qq::auto_module;
+
+/// 使用 #[auto_module] 时不需要在 module! 中列出每个handler
+#[auto_module(id = "greeting", name = "问候")]
+pub mod greeting {
+    use proc_qq::re_exports::ricq;
+    use proc_qq::{
+        event, result, scheduler_job, EventResult, MessageChainParseTrait, MessageContentTrait,
+        MessageEvent, MessageSendToSourceTrait,
+    };
+    use std::sync::Arc;
+
+    /// 回复早上好
+    #[event(trim_eq = "早上好")]
+    async fn morning(event: &MessageEvent) -> anyhow::Result<bool> {
+        event
+            .send_message_to_source("早上好".parse_message_chain())
+            .await?;
+        Ok(true)
+    }
+
+    /// 回复晚安
+    #[event]
+    async fn night(event: &MessageEvent) -> anyhow::Result<bool> {
+        if event.message_content().trim() != "晚安" {
+            return Ok(false);
+        }
+        event
+            .send_message_to_source("晚安".parse_message_chain())
+            .await?;
+        Ok(true)
+    }
+
+    #[result]
+    async fn on_greeting_result(result: &EventResult) -> anyhow::Result<bool> {
+        if let EventResult::Exception(info, err) = result {
+            if info.module_id == "greeting" {
+                tracing::warn!("{} : 问候失败 : {}", info.handle_name, err);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    #[scheduler_job(repeat = 3600)]
+    async fn hourly(_client: Arc<ricq::Client>) -> anyhow::Result<()> {
+        tracing::info!("整点报时");
+        Ok(())
+    }
+}
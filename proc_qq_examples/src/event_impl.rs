@@ -0,0 +1,58 @@
+use proc_qq::{
+    event_impl, module, MessageChainParseTrait, MessageContentTrait, MessageEvent,
+    MessageSendToSourceTrait, Module,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 带有状态的handler, 不需要把状态放在全局变量中
+pub struct Counter {
+    name: String,
+    count: AtomicU64,
+}
+
+impl Counter {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            count: AtomicU64::new(0),
+        }
+    }
+}
+
+#[event_impl]
+impl Counter {
+    /// 计数加一
+    // 每个实例分别计算冷却
+    #[event(trim_eq = "打卡", cooldown = "10s")]
+    async fn add(&self, event: &MessageEvent) -> anyhow::Result<bool> {
+        let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+        event
+            .send_message_to_source(
+                format!("{}: 第{}次打卡", self.name, count).parse_message_chain(),
+            )
+            .await?;
+        Ok(true)
+    }
+
+    /// 查看次数
+    #[event]
+    async fn show(&self, event: &MessageEvent) -> anyhow::Result<bool> {
+        if event.message_content().trim() != "打卡次数" {
+            return Ok(false);
+        }
+        event
+            .send_message_to_source(self.message().parse_message_chain())
+            .await?;
+        Ok(true)
+    }
+
+    fn message(&self) -> String {
+        format!("{}: 共{}次", self.name, self.count.load(Ordering::SeqCst))
+    }
+}
+
+pub fn module() -> Module {
+    let counter = Counter::new("打卡机");
+    // 变量需要加上括号, 不加括号的名称会被当作#[event]生成的handler
+    module!("counter", "打卡", (counter))
+}
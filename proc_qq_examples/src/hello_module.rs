@@ -1,9 +1,12 @@
 pub use proc_qq::re_exports::async_trait::async_trait;
-use proc_qq::re_exports::ricq::client::event::GroupMessageEvent;
+use proc_qq::re_exports::ricq::client::event::{GroupMessageEvent, GroupPokeEvent};
+use proc_qq::re_exports::ricq::msg::elem::Reply;
 use proc_qq::{
-    event, event_fn, module, LoginEvent, MessageChainParseTrait, MessageContentTrait, MessageEvent,
-    MessageEventProcess, MessageSendToSourceTrait, Module,
+    command_group, event, event_fn, module, CommandArg, GroupFile, LoginEvent,
+    MessageChainParseTrait, MessageContentTrait, MessageEvent, MessageEventProcess,
+    MessageSendToSourceTrait, Module, Target,
 };
+use std::time::Duration;
 
 /// 登录的时候调用 (但是不一定登录成功)
 #[event]
@@ -12,8 +15,8 @@ async fn login(event: &LoginEvent) -> anyhow::Result<bool> {
     Ok(false)
 }
 
-/// 任何消息都调用
-#[event]
+/// 任何消息都调用, 10秒没有处理完成会被取消
+#[event(timeout = "10s")]
 async fn print(event: &MessageEvent) -> anyhow::Result<bool> {
     let content = event.message_content();
     if content.eq("你好") {
@@ -66,6 +69,22 @@ async fn handle(event: &MessageEvent) -> anyhow::Result<bool> {
     Ok(true)
 }
 
+/// 正则的命名分组可以作为参数, 参数名与分组名一致, 分组没有匹配到时可以使用Option
+#[event(regexp = "^禁言(?P<uin>\\d+) (?P<minutes>\\d+)(?P<reason> .+)?$")]
+async fn handle_capture(
+    event: &MessageEvent,
+    uin: i64,
+    minutes: u32,
+    reason: Option<String>,
+) -> anyhow::Result<bool> {
+    event
+        .send_message_to_source(
+            format!("禁言 {} {}分钟, 原因 : {:?}", uin, minutes, reason).parse_message_chain(),
+        )
+        .await?;
+    Ok(true)
+}
+
 /// 多个规则, 支持 trim_regexp trim_eq all any not regexp eq
 /// 支持嵌套使用 all(not(any(),eq = ""))
 #[event(trim_regexp = "^a([\\S\\s]+)?$", trim_regexp = "^([\\S\\s]+)?b$")]
@@ -76,6 +95,32 @@ async fn handle2(event: &MessageEvent) -> anyhow::Result<bool> {
     Ok(true)
 }
 
+/// 限定群号、QQ号、会话类型(group friend temp)以及at机器人
+/// 同样支持嵌套使用 any(group = [123456], user = [654321])
+#[event(
+    any(group = [123456], user = [654321]),
+    chat = "group|temp",
+    at_bot,
+    trim_regexp = "早安$"
+)]
+async fn handle_scope(event: &MessageEvent) -> anyhow::Result<bool> {
+    event
+        .send_message_to_source("早".parse_message_chain())
+        .await?;
+    Ok(true)
+}
+
+/// 其他事件也可以使用 group user target_is_bot 以及 all any not
+#[event(target_is_bot, not(user = [123456]))]
+async fn poke_bot(event: &GroupPokeEvent) -> anyhow::Result<bool> {
+    tracing::info!(
+        "{} 在群 {} 中戳了机器人",
+        event.inner.sender,
+        event.inner.group_code
+    );
+    Ok(true)
+}
+
 /// 解决调用函数生命周期问题, 使用self调用 event_fn
 
 #[event]
@@ -158,6 +203,117 @@ async fn handle11(
     Ok(true)
 }
 
+// 引号中的空白不会分隔参数 (/say_to "hello world" 3), {text..} 取得剩下的全部文本
+#[event(bot_command = "/say_to {word} {times} {text..}")]
+async fn handle12(
+    _message: &MessageEvent,
+    word: String,
+    times: u8,
+    text: Option<String>,
+) -> anyhow::Result<bool> {
+    println!("handle12。 : {:?} , {:?} , {:?} ", word, times, text);
+    Ok(true)
+}
+
+// 别名, 可以省略的参数 和 flag (/禁言 @abc 10 --silent -r 刷屏)
+// usage = true : 关键字匹配但是参数不正确时回复用法
+#[event(
+    bot_command = "/mute|/禁言 {user} [{time}] --silent|-s --reason|-r {reason}",
+    usage = true
+)]
+async fn handle13(
+    _message: &MessageEvent,
+    user: ::proc_qq::re_exports::ricq::msg::elem::At,
+    time: Option<u64>,
+    silent: bool,
+    reason: Option<String>,
+) -> anyhow::Result<bool> {
+    println!(
+        "handle13。 : {:?} , {:?} , {:?} , {:?} ",
+        user, time, silent, reason
+    );
+    Ok(true)
+}
+
+// 自定义参数类型, 按照变体名或别名匹配 (/天气预报 开, /天气预报 off)
+#[derive(CommandArg, Debug)]
+enum Mode {
+    #[arg(alias = "开", alias = "打开")]
+    On,
+    #[arg(alias = "关")]
+    Off,
+}
+
+// 元组结构体使用内部类型的匹配
+#[derive(CommandArg, Debug)]
+struct Times(u8);
+
+#[event(bot_command = "/天气预报 {mode} [{times}]")]
+async fn handle14(
+    _message: &MessageEvent,
+    mode: Mode,
+    times: Option<Times>,
+) -> anyhow::Result<bool> {
+    println!(
+        "handle14。 : {:?} , {:?} ",
+        mode,
+        times.map(|Times(times)| times)
+    );
+    Ok(true)
+}
+
+// 没有空白分隔时按照前缀匹配
+#[event(bot_command = "{mode}天气预报")]
+async fn handle15(_message: &MessageEvent, mode: Mode) -> anyhow::Result<bool> {
+    println!("handle15。 : {:?} ", mode);
+    Ok(true)
+}
+
+// 回复一条消息并发送 /复读, 回复的消息不在消息内容中, 可以写在bot_command的任意位置
+#[event(bot_command = "/复读 {reply}")]
+async fn handle16(message: &MessageEvent, reply: Reply) -> anyhow::Result<bool> {
+    message.send_message_to_source(reply.elements).await?;
+    Ok(true)
+}
+
+// 上传群文件时回复文件的名称和大小, 群文件消息中没有文字
+#[event(bot_command = "{file}")]
+async fn handle17(message: &MessageEvent, file: GroupFile) -> anyhow::Result<bool> {
+    message
+        .send_message_to_source(format!("{} ({}字节)", file.name, file.size).parse_message_chain())
+        .await?;
+    Ok(true)
+}
+
+// 指令组, 组内的指令为 /管理 禁言 @某人, /管理 踢出 @某人, 只发送 /管理 时回复组内的指令
+#[command_group(
+    name = "/管理|/admin",
+    require = "group_admin",
+    require_reply = "{require}才能使用"
+)]
+mod admin {
+    use super::*;
+
+    /// 禁言群成员
+    #[event(bot_command = "禁言|ban {user} [{time}]")]
+    async fn ban(
+        _message: &MessageEvent,
+        user: Target,
+        time: Option<Duration>,
+    ) -> anyhow::Result<bool> {
+        println!("ban : {:?} , {:?} ", user.uin(), time);
+        Ok(true)
+    }
+
+    /// 踢出群成员
+    // require与组的require合并, 需要发送者是管理员并且机器人是管理员
+    #[event(bot_command = "踢出|kick {user}", require = "bot_admin")]
+    async fn kick(_message: &MessageEvent, user: Target) -> anyhow::Result<bool> {
+        println!("kick : {:?} ", user.uin());
+        Ok(true)
+    }
+}
+
 /// module
 
 // 这里尽可能多的展示了示例，同时也为了ci check, 搬运代码建议删掉一部分使用
@@ -171,6 +327,9 @@ pub fn module() -> Module {
         group_hello,
         handle,
         handle2,
+        handle_capture,
+        handle_scope,
+        poke_bot,
         handle3,
         handle4,
         handle5,
@@ -180,5 +339,14 @@ pub fn module() -> Module {
         handle9,
         handle10,
         handle11,
+        handle12,
+        handle13,
+        handle14,
+        handle15,
+        handle16,
+        handle17,
+        admin::admin_help,
+        admin::ban,
+        admin::kick,
     )
 }
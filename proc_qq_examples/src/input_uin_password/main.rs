@@ -6,9 +6,9 @@ use proc_qq::re_exports::async_trait::async_trait;
 use proc_qq::re_exports::ricq;
 use proc_qq::re_exports::ricq::version::ANDROID_PHONE;
 use proc_qq::*;
-use proc_qq_examples::init_tracing_subscriber;
 use proc_qq_examples::result_handlers;
 use proc_qq_examples::{hello_module, scheduler_handlers};
+use proc_qq_examples::{init_tracing_subscriber, HelloConfig};
 
 #[tokio::main]
 async fn main() {
@@ -30,6 +30,7 @@ async fn main() {
         .modules(vec![hello_module::module()])
         .result_handlers(vec![result_handlers::on_result {}.into()])
         .schedulers(vec![scheduler_handlers::scheduler()])
+        .data(HelloConfig { friend_uin: 123123 })
         .build()
         .await
         .unwrap();
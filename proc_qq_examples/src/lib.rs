@@ -2,10 +2,17 @@ use tracing::Level;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
+pub mod auto_module;
+pub mod event_impl;
 pub mod hello_module;
 pub mod result_handlers;
 pub mod scheduler_handlers;
 
+/// 通过 ClientBuilder::data 设置, 在handler中使用 Data<HelloConfig> 获取
+pub struct HelloConfig {
+    pub friend_uin: i64,
+}
+
 pub fn init_tracing_subscriber() {
     tracing_subscriber::registry()
         .with(
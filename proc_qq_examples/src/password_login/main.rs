@@ -4,10 +4,12 @@ use std::time::Duration;
 use proc_qq::re_exports::ricq;
 use proc_qq::re_exports::ricq::version::ANDROID_PHONE;
 use proc_qq::*;
+use proc_qq_examples::auto_module::greeting;
+use proc_qq_examples::event_impl;
 use proc_qq_examples::hello_module;
-use proc_qq_examples::init_tracing_subscriber;
 use proc_qq_examples::result_handlers;
 use proc_qq_examples::scheduler_handlers;
+use proc_qq_examples::{init_tracing_subscriber, HelloConfig};
 
 #[tokio::main]
 async fn main() {
@@ -15,6 +17,9 @@ async fn main() {
     let qsign =
         ricq::qsign::QSignClient::new("url".to_owned(), "key ".to_owned(), Duration::from_secs(60))
             .expect("qsign client build err");
+    // #[auto_module] 生成的结果处理器
+    let mut on_results = greeting::result_handlers();
+    on_results.push(result_handlers::on_result {}.into());
     let client = ClientBuilder::new()
         .authentication(Authentication::UinPasswordMd5(123456, [0; 16]))
         .show_slider_pop_menu_if_possible()
@@ -24,9 +29,14 @@ async fn main() {
         .session_store(Box::new(FileSessionStore {
             path: "session.token".to_string(),
         }))
-        .modules(vec![hello_module::module()])
-        .result_handlers(vec![result_handlers::on_result {}.into()])
-        .schedulers(vec![scheduler_handlers::scheduler()])
+        .modules(vec![
+            hello_module::module(),
+            greeting::module(),
+            event_impl::module(),
+        ])
+        .result_handlers(on_results)
+        .schedulers(vec![scheduler_handlers::scheduler(), greeting::scheduler()])
+        .data(HelloConfig { friend_uin: 123123 })
         .build()
         .await
         .unwrap();
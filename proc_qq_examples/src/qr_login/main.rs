@@ -3,9 +3,9 @@ use proc_qq::*;
 use std::sync::Arc;
 
 use proc_qq_examples::hello_module;
-use proc_qq_examples::init_tracing_subscriber;
 use proc_qq_examples::result_handlers;
 use proc_qq_examples::scheduler_handlers;
+use proc_qq_examples::{init_tracing_subscriber, HelloConfig};
 
 #[tokio::main]
 async fn main() {
@@ -19,6 +19,7 @@ async fn main() {
         .modules(vec![hello_module::module()])
         .result_handlers(vec![result_handlers::on_result {}.into()])
         .schedulers(vec![scheduler_handlers::scheduler()])
+        .data(HelloConfig { friend_uin: 123123 })
         .build()
         .await
         .unwrap();
@@ -15,6 +15,34 @@ pub async fn on_result(result: &EventResult) -> anyhow::Result<bool> {
                 err
             );
         }
+        EventResult::Skipped(info) => {
+            tracing::info!("{} : {} : 模块已关闭", info.module_id, info.handle_name);
+        }
+        EventResult::Timeout(info) => {
+            tracing::info!("{} : {} : 处理超时", info.module_id, info.handle_name);
+        }
+        EventResult::Throttled(info, remaining) => {
+            tracing::info!(
+                "{} : {} : 调用太频繁, 还需要等待 {:?}",
+                info.module_id,
+                info.handle_name,
+                remaining
+            );
+        }
+        EventResult::Denied(info, require) => {
+            tracing::info!(
+                "{} : {} : 没有权限, 需要 {}",
+                info.module_id,
+                info.handle_name,
+                require.description()
+            );
+        }
+        EventResult::CommandParseError(info, error) => {
+            tracing::info!("{} : {} : {}", info.module_id, info.handle_name, error);
+        }
+        EventResult::MiddlewareException(err) => {
+            tracing::info!("中间件遇到了错误 : {}", err);
+        }
     }
     Ok(false)
 }
@@ -2,14 +2,16 @@ use anyhow::Context;
 use proc_qq::re_exports::ricq::Client;
 use proc_qq::re_exports::ricq_core::msg::elem::Text;
 use proc_qq::re_exports::ricq_core::msg::MessageChain;
-use proc_qq::{scheduler, scheduler_job, MessageChainAppendTrait, Scheduler};
+use proc_qq::{scheduler, scheduler_job, Data, MessageChainAppendTrait, Scheduler};
 use std::sync::Arc;
 
+use crate::HelloConfig;
+
 /// 每1分钟发送一次 Hello std::time::Duration  秒
 #[scheduler_job(repeat = 60)]
-async fn handle_scheduler(c: Arc<Client>) -> anyhow::Result<()> {
+async fn handle_scheduler(c: Arc<Client>, config: Data<HelloConfig>) -> anyhow::Result<()> {
     let chain = MessageChain::default().append(Text::new("Hello".to_owned()));
-    c.send_friend_message(123123, chain)
+    c.send_friend_message(config.friend_uin, chain)
         .await
         .with_context(|| "sent message failed")?;
     Ok(())
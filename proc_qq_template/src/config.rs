@@ -29,6 +29,9 @@ pub struct Config {
     pub account: Account,
     pub mongo: Mongo,
     pub redis: Redis,
+    /// 超级用户的QQ号
+    #[serde(default)]
+    pub superusers: Vec<i64>,
 }
 
 pub(crate) async fn load_config() -> anyhow::Result<Config> {
@@ -46,6 +49,7 @@ pub(crate) async fn load_config() -> anyhow::Result<Config> {
             host: "127.0.0.1".to_string(),
             port: 6379,
         },
+        superusers: vec![],
     };
     if Path::new(CONFIG_FILE_PATH).exists() {
         config = serde_yaml::from_str(&std::fs::read_to_string(CONFIG_FILE_PATH)?)?;
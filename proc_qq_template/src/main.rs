@@ -15,6 +15,7 @@ use tracing_subscriber::util::SubscriberInitExt;
 
 mod config;
 mod database;
+mod middlewares;
 mod modules;
 mod utils;
 
@@ -39,6 +40,8 @@ async fn main() -> anyhow::Result<()> {
         .qsign(Some(Arc::new(qsign)))
         .show_slider_pop_menu_if_possible()
         .modules(modules::all_modules())
+        .middlewares(middlewares::all_middlewares())
+        .superusers(config.superusers.clone())
         .build()
         .await
         .unwrap();
@@ -0,0 +1,28 @@
+/// 屏蔽一些特殊账号的消息, 以及自己发出的消息
+use proc_qq::re_exports::async_trait::async_trait;
+use proc_qq::re_exports::ricq::handler::QEvent;
+use proc_qq::Middleware;
+
+pub struct IgnoreMiddleware {}
+
+fn in_ignore_list(uin: i64) -> bool {
+    let list: Vec<i64> = vec![
+        2854196310, // Q群管家
+        2854196312, // 表情包老铁
+        2854196306, // 微软小冰
+    ];
+    list.contains(&uin)
+}
+
+#[async_trait]
+impl Middleware for IgnoreMiddleware {
+    async fn before(&self, event: &mut QEvent) -> anyhow::Result<bool> {
+        let (client, from_uin) = match event {
+            QEvent::GroupMessage(e) => (&e.client, e.inner.from_uin),
+            QEvent::FriendMessage(e) => (&e.client, e.inner.from_uin),
+            QEvent::GroupTempMessage(e) => (&e.client, e.inner.from_uin),
+            _ => return Ok(false),
+        };
+        Ok(from_uin == client.uin().await || in_ignore_list(from_uin))
+    }
+}
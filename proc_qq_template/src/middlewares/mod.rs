@@ -0,0 +1,14 @@
+use lazy_static::lazy_static;
+use proc_qq::Middleware;
+use std::sync::Arc;
+
+mod ignore;
+
+lazy_static! {
+    static ref MIDDLEWARES: Arc<Vec<Box<dyn Middleware>>> =
+        Arc::new(vec![Box::new(ignore::IgnoreMiddleware {})]);
+}
+
+pub(crate) fn all_middlewares() -> Arc<Vec<Box<dyn Middleware>>> {
+    MIDDLEWARES.clone()
+}
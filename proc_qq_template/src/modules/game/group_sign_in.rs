@@ -181,7 +181,12 @@ pub(crate) async fn init_data_base() -> anyhow::Result<()> {
 }
 
 pub(crate) fn module() -> Module {
-    module!(ID, NAME, on_message)
+    module!(
+        ID,
+        NAME,
+        description = "在群中发送 \"签到\" 获得金币, \"钱包\" 查询金币剩余量",
+        on_message,
+    )
 }
 
 const COLLECTION_NAME: &str = "game.group_sign_in";
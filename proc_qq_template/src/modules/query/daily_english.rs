@@ -4,7 +4,7 @@ use crate::utils::local::{join_paths, template_dir};
 use crate::utils::CanReply;
 use anyhow::Context;
 use proc_qq::re_exports::ricq::device::random_uuid;
-use proc_qq::{event, module, MessageContentTrait, Module};
+use proc_qq::{event, module, Module};
 use proc_qq::{MessageEvent, MessageSendToSourceTrait};
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
@@ -15,20 +15,20 @@ const ID: &str = "daily_english";
 const NAME: &str = "每日英语";
 
 pub(crate) fn module() -> Module {
-    module!(ID, NAME, on_message)
+    module!(ID, NAME, description = "每天一句英语", on_message, on_temp)
 }
 
-#[event]
+/// 发送今天的英语句子和语音
+#[event(eq = "每日英语", chat = "group|friend")]
 async fn on_message(message: &MessageEvent) -> anyhow::Result<bool> {
-    if message.message_content().eq(NAME) {
-        if message.is_temp_message() {
-            message.reply_text("此功能不支持临时消息").await?;
-            return Ok(true);
-        }
-        reply_daily_english(message).await?;
-        return Ok(true);
-    }
-    Ok(false)
+    reply_daily_english(message).await?;
+    Ok(true)
+}
+
+#[event(eq = "每日英语", chat = "temp")]
+async fn on_temp(message: &MessageEvent) -> anyhow::Result<bool> {
+    message.reply_text("此功能不支持临时消息").await?;
+    Ok(true)
 }
 
 async fn reply_daily_english(message: &MessageEvent) -> anyhow::Result<()> {
@@ -14,7 +14,15 @@ static MENU: &'static str = "图库 (请直接回复功能名) : \n ❤️ 随
 static UA: &'static str = "Mozilla/5.0 (Linux; Android 6.0; Nexus 5 Build/MRA58N) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/98.0.4758.80 Mobile Safari/537.36";
 
 pub fn module() -> Module {
-    module!(ID, NAME, on_message)
+    module!(
+        ID,
+        NAME,
+        description = "随机图片",
+        on_message,
+        on_laopo,
+        on_dongman,
+        on_temp,
+    )
 }
 
 fn no_temp_message() -> MessageChain {
@@ -33,33 +41,54 @@ async fn on_message(event: &MessageEvent) -> anyhow::Result<bool> {
                 .await?;
         }
         Ok(true)
-    } else if content.eq("随机老婆") {
-        if event.is_temp_message() {
-            event.send_message_to_source(no_temp_message()).await?;
-            return Ok(true);
-        }
-        let img = get_laopo_img().await?.to_vec();
-        let img = event.upload_image_to_source(img).await?;
-        event
-            .send_message_to_source(event.make_reply_chain().await.append(img))
-            .await?;
-        Ok(true)
-    } else if content.eq("动漫壁纸") {
-        if event.is_temp_message() {
-            event.send_message_to_source(no_temp_message()).await?;
-            return Ok(true);
-        }
-        let img = get_dongman_img().await?.to_vec();
-        let img = event.upload_image_to_source(img).await?;
-        event
-            .send_message_to_source(event.make_reply_chain().await.append(img))
-            .await?;
-        Ok(true)
     } else {
         Ok(false)
     }
 }
 
+// 临时会话不能使用图库
+#[event(chat = "temp", any(eq = "随机老婆", eq = "动漫壁纸"))]
+async fn on_temp(event: &MessageEvent) -> anyhow::Result<bool> {
+    event.send_message_to_source(no_temp_message()).await?;
+    Ok(true)
+}
+
+/// 随机一张老婆的图片, 每个人30秒内最多3次
+#[event(
+    bot_command = "随机老婆",
+    chat = "group|friend",
+    cooldown = "30s",
+    per = "user",
+    burst = 3,
+    cooldown_reply = "太快啦, 请{remaining}秒后再试"
+)]
+async fn on_laopo(event: &MessageEvent) -> anyhow::Result<bool> {
+    let img = get_laopo_img().await?.to_vec();
+    let img = event.upload_image_to_source(img).await?;
+    event
+        .send_message_to_source(event.make_reply_chain().await.append(img))
+        .await?;
+    Ok(true)
+}
+
+/// 随机一张动漫壁纸, 每个人30秒内最多3次
+#[event(
+    bot_command = "动漫壁纸",
+    chat = "group|friend",
+    cooldown = "30s",
+    per = "user",
+    burst = 3,
+    cooldown_reply = "太快啦, 请{remaining}秒后再试"
+)]
+async fn on_dongman(event: &MessageEvent) -> anyhow::Result<bool> {
+    let img = get_dongman_img().await?.to_vec();
+    let img = event.upload_image_to_source(img).await?;
+    event
+        .send_message_to_source(event.make_reply_chain().await.append(img))
+        .await?;
+    Ok(true)
+}
+
 async fn get_laopo_img() -> anyhow::Result<bytes::Bytes> {
     let text = reqwest::ClientBuilder::new()
         .danger_accept_invalid_certs(true)